@@ -1,27 +1,68 @@
-use crate::types::{AccountPreference, AppCredentials, GoogleAuthToken, Preferences};
-use crate::utils::{parse_event_datetime, with_local_timezone, EventGroups};
-use chrono::{DateTime, Timelike};
+use crate::cache::EventCache;
+use crate::types::{
+    AccountPreference, AccountSyncStatus, AlertStage, AllDayPolicy, AppCredentials, EventConflict,
+    EventSource, GoogleAuthToken, InvitationPolicy, Language, LeadTimes, Preferences, SyncState,
+    DEFAULT_AGENDA_DAYS,
+};
+use crate::utils::{
+    all_day_covers, find_conflicts, is_all_day, is_meeting, local_now, local_timezone,
+    parse_event_datetime, with_local_timezone, EventGroups,
+};
+use chrono::{DateTime, TimeZone, Timelike, Utc};
+use chrono_tz::Tz;
 use futures::TryFutureExt;
-use google_calendar::events::Events;
-use google_calendar::types::Event;
-use google_calendar::{types::MinAccessRole, Client};
+use google_calendar::types::{CalendarListEntry, Event};
+use google_calendar::{Client, ClientError};
+use reqwest::header::RETRY_AFTER;
+use reqwest::{StatusCode, Url};
+use std::collections::HashMap;
 use std::sync::{Arc, Mutex};
 use std::time::{SystemTime, UNIX_EPOCH};
 
 pub struct Calendars {
-    accounts: tokio::sync::Mutex<Vec<CalenderAccount>>,
+    accounts: Arc<tokio::sync::Mutex<Vec<CalenderAccount>>>,
+    /// Bumped whenever an account is added or removed, polls fetched for an older
+    /// generation are dropped
+    generation: Mutex<u64>,
     config: AppCredentials,
     pub event_groups: Mutex<EventGroups>,
     events: Mutex<Vec<Event>>,
+    /// Every account and calendar an event was fetched from, keyed by event id
+    sources: Mutex<HashMap<String, Vec<EventSource>>>,
+    /// Lead times taken from Google reminders, keyed by event id
+    reminder_lead_times: Mutex<HashMap<String, LeadTimes>>,
+    conflicts: Mutex<Vec<EventConflict>>,
+    /// Days ahead, today included, that events are fetched and grouped for
+    agenda_days: Mutex<i64>,
+    /// Timezone override from the preferences, events are grouped by its days
+    timezone: Mutex<Option<Tz>>,
+    cache: Mutex<EventCache>,
+    /// Events come from the cache or an earlier poll because the last poll failed
+    stale: Mutex<bool>,
+    /// Sync diagnostics keyed by account email
+    sync_status: Mutex<HashMap<String, AccountSyncStatus>>,
+    /// Sources of the events missing since the last poll, tells a cancelled event from
+    /// one whose account failed to sync
+    vanished_sources: Mutex<HashMap<String, Vec<EventSource>>>,
 }
 
 impl Default for Calendars {
     fn default() -> Self {
         Calendars {
             config: AppCredentials::default(),
-            accounts: tokio::sync::Mutex::new(vec![]),
+            accounts: Arc::new(tokio::sync::Mutex::new(vec![])),
+            generation: Mutex::new(0),
             event_groups: Mutex::new(EventGroups::default()),
             events: Mutex::new(Vec::new()),
+            sources: Mutex::new(HashMap::new()),
+            reminder_lead_times: Mutex::new(HashMap::new()),
+            conflicts: Mutex::new(Vec::new()),
+            agenda_days: Mutex::new(DEFAULT_AGENDA_DAYS),
+            timezone: Mutex::new(None),
+            cache: Mutex::new(EventCache::default()),
+            stale: Mutex::new(false),
+            sync_status: Mutex::new(HashMap::new()),
+            vanished_sources: Mutex::new(HashMap::new()),
         }
     }
 }
@@ -31,20 +72,144 @@ impl Calendars {
         tokens: Vec<GoogleAuthToken>,
         config: AppCredentials,
         preferences: &Preferences,
+        cache: EventCache,
     ) -> Self {
-        let accounts = futures::future::join_all(tokens.iter().map(|token| async {
+        let mut accounts = futures::future::join_all(tokens.iter().map(|token| async {
             let email = token.user.clone().unwrap().email;
             let account_preferences = preferences.get_account_preference(&email);
             CalenderAccount::new(token.to_owned(), config.clone(), account_preferences).await
         }))
         .await;
 
-        Calendars {
+        // calendar lists could not be fetched while offline, fall back to the cached ones
+        for account in accounts.iter_mut() {
+            let email = account.email();
+            if account.calendar_list.is_empty() {
+                if let Some(calendar_list) = cache.calendar_lists.get(&email) {
+                    println!("Using cached calendar list for {}", &email);
+                    account.calendar_list = calendar_list.clone();
+                }
+            }
+            if cache.calendar_lists.contains_key(&email) {
+                *account.last_synced_at.lock().unwrap() = cache.synced_at;
+            }
+            *account.last_events.lock().unwrap() = cache
+                .events
+                .iter()
+                .flat_map(|event| {
+                    cache
+                        .sources
+                        .get(&event.id)
+                        .into_iter()
+                        .flatten()
+                        .filter(|source| source.account == email)
+                        .map(move |source| (source.calendar_id.clone(), event.clone()))
+                })
+                .collect();
+        }
+
+        let sync_status = accounts
+            .iter()
+            .map(|account| (account.email(), account.sync_status()))
+            .collect();
+        let calendars = Calendars {
             config,
-            accounts: tokio::sync::Mutex::new(accounts),
+            accounts: Arc::new(tokio::sync::Mutex::new(accounts)),
+            generation: Mutex::new(0),
             event_groups: Mutex::new(EventGroups::default()),
             events: Mutex::new(Vec::new()),
+            sources: Mutex::new(HashMap::new()),
+            reminder_lead_times: Mutex::new(HashMap::new()),
+            conflicts: Mutex::new(Vec::new()),
+            agenda_days: Mutex::new(preferences.agenda_days()),
+            timezone: Mutex::new(preferences.timezone()),
+            cache: Mutex::new(EventCache::default()),
+            stale: Mutex::new(false),
+            sync_status: Mutex::new(sync_status),
+            vanished_sources: Mutex::new(HashMap::new()),
+        };
+        calendars.load_cache(cache);
+        calendars
+    }
+
+    /// Show the cached events until the next successful poll
+    pub fn load_cache(&self, cache: EventCache) {
+        *self.events.lock().unwrap() = cache.events.clone();
+        *self.sources.lock().unwrap() = cache.sources.clone();
+        *self.reminder_lead_times.lock().unwrap() = cache.reminder_lead_times.clone();
+        *self.stale.lock().unwrap() = cache.synced_at.is_some();
+        *self.cache.lock().unwrap() = cache;
+        self.group_events();
+    }
+
+    /// Record a successful poll, the cache file is only written when its content changed
+    fn update_cache(&self, calendar_lists: HashMap<String, Vec<CalendarListEntry>>) {
+        let mut cache = self.cache.lock().unwrap();
+        let events = self.events.lock().unwrap();
+        let sources = self.sources.lock().unwrap();
+        let reminder_lead_times = self.reminder_lead_times.lock().unwrap();
+        let changed = fingerprint(&cache.events) != fingerprint(&events)
+            || cache.sources != *sources
+            || cache.reminder_lead_times != *reminder_lead_times
+            || calendar_ids(&cache.calendar_lists) != calendar_ids(&calendar_lists);
+
+        cache.synced_at = Some(Utc::now());
+        if !changed {
+            return;
+        }
+        cache.events = events.clone();
+        cache.sources = sources.clone();
+        cache.reminder_lead_times = reminder_lead_times.clone();
+        cache.calendar_lists = calendar_lists;
+        let _ = cache.save_state();
+    }
+
+    /// Forget every account, event and the cache on logout, nothing is cached again
+    /// until an account signs in
+    pub async fn sign_out(&self) {
+        self.accounts.lock().await.clear();
+        *self.generation.lock().unwrap() += 1;
+        self.events.lock().unwrap().clear();
+        self.sources.lock().unwrap().clear();
+        self.vanished_sources.lock().unwrap().clear();
+        self.reminder_lead_times.lock().unwrap().clear();
+        self.sync_status.lock().unwrap().clear();
+        *self.stale.lock().unwrap() = false;
+        let _ = self.cache.lock().unwrap().clear();
+        self.group_events();
+    }
+
+    /// Drop the events of a removed account from the synced events and the cache
+    fn forget_account(&self, email: &str) {
+        let mut cache = self.cache.lock().unwrap();
+        let mut sources = self.sources.lock().unwrap();
+        let mut vanished_sources = self.vanished_sources.lock().unwrap();
+        for (id, event_sources) in sources.iter_mut() {
+            if event_sources.iter().all(|source| source.account == email) {
+                vanished_sources.insert(id.clone(), event_sources.clone());
+            }
+            event_sources.retain(|source| source.account != email);
         }
+        drop(vanished_sources);
+        sources.retain(|_, event_sources| !event_sources.is_empty());
+        self.events
+            .lock()
+            .unwrap()
+            .retain(|event| sources.contains_key(&event.id));
+        self.reminder_lead_times
+            .lock()
+            .unwrap()
+            .retain(|id, _| sources.contains_key(id));
+
+        cache.events = self.events.lock().unwrap().clone();
+        cache.sources = sources.clone();
+        cache.reminder_lead_times = self.reminder_lead_times.lock().unwrap().clone();
+        cache.calendar_lists.remove(email);
+        let _ = cache.save_state();
+        drop(sources);
+        drop(cache);
+
+        self.group_events();
     }
 
     /// Add new calendar account to accounts list
@@ -54,71 +219,36 @@ impl Calendars {
         preferences: &Preferences,
     ) -> Result<(), String> {
         println!("add_account::Locked---------+++++++");
-        if token.user.is_some() {
+        if let Some(user) = token.user.clone() {
             println!("Add new Account");
+            let account_preferences = preferences.get_account_preference(&user.email);
+            let account =
+                CalenderAccount::new(token, self.config.clone(), account_preferences).await;
             let mut calendar_accounts = self.accounts.lock().await;
 
             println!("Lock acquired");
-            let accounts = calendar_accounts
-                .iter()
-                .filter_map(|account| {
-                    if account.is_account(&token.user.clone().unwrap().email) {
-                        None
-                    } else {
-                        Some(account)
-                    }
-                })
-                .collect::<Vec<&CalenderAccount>>();
-            let mut tokens = accounts
-                .iter()
-                .map(|acct| acct.to_auth_token())
-                .collect::<Vec<GoogleAuthToken>>();
-            tokens.insert(tokens.len(), token);
-
-            let accounts = futures::future::join_all(tokens.iter().map(|token| async {
-                let email = token.user.clone().unwrap().email;
-                let account_preferences = preferences.get_account_preference(&email);
-                CalenderAccount::new(token.to_owned(), self.config.clone(), account_preferences)
-                    .await
-            }))
-            .await;
-
-            *calendar_accounts = accounts;
+            // the other accounts keep their backoff, last events and sync tokens
+            calendar_accounts.retain(|account| !account.is_account(&user.email));
+            calendar_accounts.push(account);
+            self.refresh_sync_status(&calendar_accounts);
             drop(calendar_accounts);
+            *self.generation.lock().unwrap() += 1;
         }
         Ok(())
     }
 
-    pub async fn remove_account(
-        &self,
-        email: String,
-        preferences: &Preferences,
-    ) -> Result<(), String> {
+    pub async fn remove_account(&self, email: String) -> Result<(), String> {
         let mut calendar_accounts = self.accounts.lock().await;
 
-        let accounts = calendar_accounts
-            .iter()
-            .filter(|account| !account.is_account(&email))
-            .collect::<Vec<&CalenderAccount>>();
-
         if calendar_accounts.len() == 1 {
             return Ok(());
         }
 
-        let tokens = accounts
-            .iter()
-            .map(|acct| acct.to_auth_token())
-            .collect::<Vec<GoogleAuthToken>>();
-
-        let accounts = futures::future::join_all(tokens.iter().map(|token| async {
-            let email = token.user.clone().unwrap().email;
-            let account_preferences = preferences.get_account_preference(&email);
-            CalenderAccount::new(token.to_owned(), self.config.clone(), account_preferences).await
-        }))
-        .await;
-
-        *calendar_accounts = accounts;
+        calendar_accounts.retain(|account| !account.is_account(&email));
+        self.refresh_sync_status(&calendar_accounts);
         drop(calendar_accounts);
+        *self.generation.lock().unwrap() += 1;
+        self.forget_account(&email);
         Ok(())
     }
 
@@ -169,6 +299,10 @@ impl Calendars {
         Ok(tokens)
     }
 
+    pub fn all_day_events(&self) -> Vec<Event> {
+        self.event_groups.lock().unwrap().all_day.clone()
+    }
+
     pub fn active_events(&self) -> Vec<Event> {
         self.event_groups.lock().unwrap().now.clone()
     }
@@ -181,18 +315,133 @@ impl Calendars {
         self.event_groups.lock().unwrap().tomorrow.clone()
     }
 
+    pub fn find_event(&self, event_id: &str) -> Option<Event> {
+        self.events
+            .lock()
+            .unwrap()
+            .iter()
+            .find(|event| event.id == event_id)
+            .cloned()
+    }
+
+    /// Accounts and calendars the event with `event_id` was fetched from
+    pub fn event_sources(&self, event_id: &str) -> Vec<EventSource> {
+        self.sources
+            .lock()
+            .unwrap()
+            .get(event_id)
+            .cloned()
+            .unwrap_or_default()
+    }
+
+    /// Lead times configured for the calendars an event belongs to, the earliest
+    /// lead time wins when the event comes from several calendars
+    pub fn lead_times(&self, event_id: &str, preferences: &Preferences) -> LeadTimes {
+        if let Some(lead_times) = self.reminder_lead_times.lock().unwrap().get(event_id) {
+            return *lead_times;
+        }
+
+        self.event_sources(event_id)
+            .iter()
+            .map(|source| preferences.get_lead_times(&source.account, &source.calendar_id))
+            .reduce(|lead_times, other| lead_times.earliest(other))
+            .unwrap_or_default()
+    }
+
+    /// Alert stages to fire for an event, given the meeting filter and the user's
+    /// response to the invitation
+    pub fn alert_stages(&self, event: &Event, preferences: &Preferences) -> Vec<AlertStage> {
+        let sources = self.event_sources(&event.id);
+        let accounts = sources
+            .iter()
+            .map(|source| source.account.clone())
+            .collect::<Vec<String>>();
+        if preferences.notify_only_meetings() && !is_meeting(event, &accounts) {
+            return vec![];
+        }
+
+        if is_all_day(event) {
+            return match preferences.all_day_events() {
+                AllDayPolicy::Off => vec![],
+                AllDayPolicy::MorningNotification => vec![AlertStage::Notification],
+            };
+        }
+
+        // the most committed response wins when the event comes from several accounts
+        let accepted = sources.is_empty()
+            || sources
+                .iter()
+                .any(|source| source.response_status == "accepted");
+        if accepted {
+            return vec![AlertStage::Notification, AlertStage::Alert];
+        }
+
+        match preferences.pending_invitations() {
+            InvitationPolicy::Alert => vec![AlertStage::Notification, AlertStage::Alert],
+            InvitationPolicy::NotifyOnly => vec![AlertStage::Notification],
+            InvitationPolicy::Ignore => vec![],
+        }
+    }
+
+    /// When each alert stage of an event is due, all-day events are notified on the
+    /// morning of their first day
+    pub fn stage_deadlines(
+        &self,
+        event: &Event,
+        preferences: &Preferences,
+    ) -> Vec<(AlertStage, DateTime<Utc>)> {
+        let stages = self.alert_stages(event, preferences);
+
+        if is_all_day(event) {
+            let morning = event
+                .start
+                .as_ref()
+                .and_then(|start| start.date)
+                .and_then(|date| date.and_hms_opt(preferences.morning_notification_hour(), 0, 0))
+                .and_then(|morning| {
+                    local_timezone(preferences.timezone())
+                        .from_local_datetime(&morning)
+                        .earliest()
+                })
+                .map(|morning| morning.with_timezone(&Utc));
+            return stages
+                .into_iter()
+                .filter_map(|stage| morning.map(|morning| (stage, morning)))
+                .collect();
+        }
+
+        let start = match event.start.clone() {
+            Some(start) => parse_event_datetime(start),
+            None => return vec![],
+        };
+        let lead_times = self.lead_times(&event.id, preferences);
+        stages
+            .into_iter()
+            .map(|stage| {
+                let deadline = start - chrono::Duration::minutes(lead_times.minutes(stage));
+                (stage, deadline)
+            })
+            .collect()
+    }
+
     pub fn group_events(&self) {
         let events = self.events.lock().unwrap();
 
-        let mut groups = EventGroups::default();
+        let mut groups = EventGroups {
+            stale: *self.stale.lock().unwrap(),
+            synced_at: self.cache.lock().unwrap().synced_at,
+            ..EventGroups::default()
+        };
 
         if events.is_empty() {
             *self.event_groups.lock().unwrap() = groups;
+            *self.conflicts.lock().unwrap() = vec![];
             return;
         }
 
-        let now = chrono::offset::Local::now();
-        let tomorrow = chrono::offset::Local::now()
+        let timezone = *self.timezone.lock().unwrap();
+        let now = local_now(timezone);
+        let tomorrow = local_now(timezone)
             .checked_add_days(chrono::naive::Days::new(1))
             .unwrap()
             .with_hour(0)
@@ -201,7 +450,7 @@ impl Calendars {
             .unwrap()
             .with_second(0)
             .unwrap();
-        let tomorrow_end = chrono::offset::Local::now()
+        let tomorrow_end = local_now(timezone)
             .checked_add_days(chrono::naive::Days::new(1))
             .unwrap()
             .with_hour(23)
@@ -210,12 +459,32 @@ impl Calendars {
             .unwrap()
             .with_second(0)
             .unwrap();
+        let agenda_days = *self.agenda_days.lock().unwrap();
+        let horizon_end = tomorrow
+            .checked_add_days(chrono::naive::Days::new((agenda_days - 1) as u64))
+            .unwrap();
 
         // println!("Now {:?} - Tomorrow {:?} - Tomorrow End {:?}", &now, &tomorrow, &tomorrow_end);
 
+        let today = now.date_naive();
         for event in events.iter() {
-            let start = with_local_timezone(parse_event_datetime(event.start.clone().unwrap()));
-            let end = with_local_timezone(parse_event_datetime(event.end.clone().unwrap()));
+            if is_all_day(event) {
+                if all_day_covers(event, today) {
+                    groups.all_day.push(event.to_owned());
+                } else if all_day_covers(event, tomorrow.date_naive()) {
+                    groups.tomorrow.push(event.to_owned());
+                } else if (2..agenda_days)
+                    .any(|days| all_day_covers(event, today + chrono::Duration::days(days)))
+                {
+                    groups.later.push(event.to_owned());
+                }
+                continue;
+            }
+
+            let start =
+                with_local_timezone(parse_event_datetime(event.start.clone().unwrap()), timezone);
+            let end =
+                with_local_timezone(parse_event_datetime(event.end.clone().unwrap()), timezone);
 
             if now > start && now < end {
                 groups.now.push(event.to_owned());
@@ -223,10 +492,15 @@ impl Calendars {
                 groups.upcoming.push(event.to_owned());
             } else if start > tomorrow && start < tomorrow_end {
                 groups.tomorrow.push(event.to_owned())
+            } else if start > tomorrow_end && start < horizon_end {
+                groups.later.push(event.to_owned())
             }
             // println!("Event: {}, start: {:?}", &event.summary, start);
         }
         println!("Polled events {:?}", events.len());
+        groups
+            .all_day
+            .sort_by_key(|event| parse_event_datetime(event.start.clone().unwrap()));
         groups
             .now
             .sort_by_key(|event| parse_event_datetime(event.end.clone().unwrap()));
@@ -236,34 +510,217 @@ impl Calendars {
         groups
             .tomorrow
             .sort_by_key(|event| parse_event_datetime(event.start.clone().unwrap()));
+        groups
+            .later
+            .sort_by_key(|event| parse_event_datetime(event.start.clone().unwrap()));
 
         // println!("Now Groups {:?}", groups.now.iter().map(|g| &g.summary).collect::<Vec<&String>>());
         // println!("Upcoming Groups {:?}", groups.upcoming.iter().map(|g| &g.summary).collect::<Vec<&String>>());
         // println!("Tomorrow Groups {:?}", groups.tomorrow.iter().map(|g| &g.summary).collect::<Vec<&String>>());
+        *self.conflicts.lock().unwrap() = self.collect_conflicts(&groups);
         *self.event_groups.lock().unwrap() = groups;
     }
 
-    pub async fn poll_events(&self) {
-        let accounts = self.accounts.lock().await;
-        let events =
-            futures::future::join_all(accounts.iter().filter(|account| !account.is_diabled()).map(
-                |account| async {
-                    println!("Account to poll: {}", account.is_diabled());
-                    account.get_calendar_events().await
-                },
-            ))
-            .await;
-
-        let events = events
+    /// Overlapping and back-to-back events across every account
+    fn collect_conflicts(&self, groups: &EventGroups) -> Vec<EventConflict> {
+        let events = groups
+            .now
             .iter()
-            .map(|e| e.to_owned())
-            .flatten()
+            .chain(groups.upcoming.iter())
+            .chain(groups.tomorrow.iter())
+            .chain(groups.later.iter())
+            .cloned()
             .collect::<Vec<Event>>();
+        let accounts = |event: &Event| {
+            self.event_sources(&event.id)
+                .into_iter()
+                .map(|source| source.account)
+                .collect::<Vec<String>>()
+        };
+
+        find_conflicts(&events)
+            .into_iter()
+            .map(|(kind, idx, other_idx)| {
+                let (event, other) = (&events[idx], &events[other_idx]);
+                EventConflict {
+                    kind,
+                    event_id: event.id.clone(),
+                    summary: event.summary.clone(),
+                    accounts: accounts(event),
+                    other_event_id: other.id.clone(),
+                    other_summary: other.summary.clone(),
+                    other_accounts: accounts(other),
+                }
+            })
+            .collect()
+    }
+
+    pub fn conflicts(&self) -> Vec<EventConflict> {
+        self.conflicts.lock().unwrap().clone()
+    }
+
+    /// Conflict warnings for a single event
+    pub fn conflict_warnings(&self, event_id: &str, language: Language) -> Vec<String> {
+        self.conflicts
+            .lock()
+            .unwrap()
+            .iter()
+            .filter_map(|conflict| conflict.warning_for(event_id, language))
+            .collect()
+    }
+
+    /// Flag the accounts the next poll will fetch as syncing, accounts backing off
+    /// keep their failing state
+    pub fn mark_syncing(&self) {
+        let now = Utc::now();
+        for status in self.sync_status.lock().unwrap().values_mut() {
+            let backing_off = status.retry_at.map_or(false, |retry_at| retry_at > now);
+            if status.state != SyncState::Disabled && !backing_off {
+                status.state = SyncState::Syncing;
+            }
+        }
+    }
+
+    /// Whether every enabled account synced on the last poll
+    pub fn is_synced(&self) -> bool {
+        self.sync_status
+            .lock()
+            .unwrap()
+            .values()
+            .all(|status| status.state == SyncState::Synced || status.state == SyncState::Disabled)
+    }
+
+    /// Whether every account an event comes from, or came from before it went missing,
+    /// synced on the last poll, so its changes or its absence can be trusted
+    pub fn is_event_synced(&self, event_id: &str) -> bool {
+        let sources = self
+            .sources
+            .lock()
+            .unwrap()
+            .get(event_id)
+            .cloned()
+            .or_else(|| self.vanished_sources.lock().unwrap().get(event_id).cloned());
+        let sources = match sources {
+            Some(sources) => sources,
+            None => return self.is_synced(),
+        };
+
+        // removed accounts have no status left, their events are gone
+        let sync_status = self.sync_status.lock().unwrap();
+        sources.iter().all(|source| {
+            sync_status.get(&source.account).map_or(true, |status| {
+                status.state == SyncState::Synced || status.state == SyncState::Disabled
+            })
+        })
+    }
+
+    /// Rebuild the sync diagnostics when an account was added or removed
+    fn refresh_sync_status(&self, accounts: &[CalenderAccount]) {
+        *self.sync_status.lock().unwrap() = accounts
+            .iter()
+            .map(|account| (account.email(), account.sync_status()))
+            .collect();
+    }
+
+    /// Sync diagnostics of every account as of the last poll
+    pub fn sync_status(&self) -> Vec<AccountSyncStatus> {
+        let mut sync_status = self
+            .sync_status
+            .lock()
+            .unwrap()
+            .values()
+            .cloned()
+            .collect::<Vec<AccountSyncStatus>>();
+        sync_status.sort_by(|a, b| a.account.cmp(&b.account));
+        sync_status
+    }
+
+    /// Fetch events of every enabled account, returns whether the event set changed
+    pub async fn poll_events(&self) -> bool {
+        let polled = self.poll_request().fetch().await;
+        self.apply_poll(polled)
+    }
+
+    /// Everything a poll needs, so events can be fetched without holding `Calendars`
+    pub fn poll_request(&self) -> PollRequest {
+        PollRequest {
+            accounts: self.accounts.clone(),
+            agenda_days: *self.agenda_days.lock().unwrap(),
+            timezone: *self.timezone.lock().unwrap(),
+            generation: *self.generation.lock().unwrap(),
+        }
+    }
+
+    /// Merge and group the events of a poll, returns whether the event set changed
+    pub fn apply_poll(&self, polled: PolledEvents) -> bool {
+        if polled.generation != *self.generation.lock().unwrap() {
+            println!("Accounts changed during the poll, dropping its events");
+            return false;
+        }
+        let results = polled.results;
+        // no enabled account is signed in, e.g. after a logout
+        let signed_in = !results.is_empty();
+
+        *self.sync_status.lock().unwrap() = polled.sync_status;
+
+        // accounts that failed keep their last good events, the data is only stale
+        // when no account could be synced
+        let stale = !results.is_empty() && results.iter().all(|(_, fresh)| !fresh);
+        if stale {
+            println!("Every account failed to sync, keeping stale events");
+        }
+        *self.stale.lock().unwrap() = stale;
+        let events = results
+            .into_iter()
+            .map(|(events, _)| events)
+            .collect::<Vec<Vec<(EventSource, Event, Option<LeadTimes>)>>>();
+
+        // the same meeting shows up once per invited account, merge the copies into
+        // a single event that keeps track of every account and calendar
+        let mut merged_ids: HashMap<(String, Option<DateTime<Utc>>), String> = HashMap::new();
+        let mut sources: HashMap<String, Vec<EventSource>> = HashMap::new();
+        let mut reminder_lead_times: HashMap<String, LeadTimes> = HashMap::new();
+        let mut merged_events: Vec<Event> = vec![];
+        for (source, event, lead_times) in events.into_iter().flatten() {
+            let key = event_key(&event);
+            let id = match merged_ids.get(&key) {
+                Some(id) => id.clone(),
+                None => {
+                    merged_ids.insert(key, event.id.clone());
+                    let id = event.id.clone();
+                    merged_events.push(event);
+                    id
+                }
+            };
+
+            if let Some(lead_times) = lead_times {
+                reminder_lead_times
+                    .entry(id.clone())
+                    .and_modify(|current| *current = current.earliest(lead_times))
+                    .or_insert(lead_times);
+            }
+            sources.entry(id).or_default().push(source);
+        }
+        let events = merged_events;
         println!("Poll events {:?}", events.len());
 
+        let changed = fingerprint(&self.events.lock().unwrap()) != fingerprint(&events);
         *self.events.lock().unwrap() = events;
+        {
+            let mut current_sources = self.sources.lock().unwrap();
+            *self.vanished_sources.lock().unwrap() = current_sources
+                .drain()
+                .filter(|(id, _)| !sources.contains_key(id))
+                .collect();
+            *current_sources = sources;
+        }
+        *self.reminder_lead_times.lock().unwrap() = reminder_lead_times;
+        if !stale && signed_in {
+            self.update_cache(polled.calendar_lists);
+        }
 
         self.group_events();
+        changed
     }
 
     pub async fn set_preferences(&self, email: String, preferences: &Preferences) {
@@ -285,12 +742,329 @@ impl Calendars {
         drop(calendar_accounts);
         self.poll_events().await;
     }
+
+    /// Apply an account's Google reminders preference to the synced events right
+    /// away instead of on the next poll
+    pub async fn set_use_google_reminders(&self, email: String, preferences: &Preferences) {
+        let accounts = self.accounts.lock().await;
+        if let Some(account) = accounts.iter().find(|account| account.is_account(&email)) {
+            account
+                .set_preferences(preferences.get_account_preference(&email))
+                .await;
+        }
+
+        // each account keeps its own copy of a merged event, match them by key
+        let merged_ids = self
+            .events
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|event| (event_key(event), event.id.clone()))
+            .collect::<HashMap<(String, Option<DateTime<Utc>>), String>>();
+        let mut reminder_lead_times: HashMap<String, LeadTimes> = HashMap::new();
+        for account in accounts.iter().filter(|account| account.is_enabled()) {
+            if !account.preferences.lock().await.use_google_reminders {
+                continue;
+            }
+            let events = account.last_events.lock().unwrap().clone();
+            for (calendar_id, event) in events.iter() {
+                let id = match merged_ids.get(&event_key(event)) {
+                    Some(id) => id,
+                    None => continue,
+                };
+                if let Some(lead_times) = account.reminder_lead_times(calendar_id, event) {
+                    reminder_lead_times
+                        .entry(id.clone())
+                        .and_modify(|current| *current = current.earliest(lead_times))
+                        .or_insert(lead_times);
+                }
+            }
+        }
+        *self.reminder_lead_times.lock().unwrap() = reminder_lead_times;
+    }
+
+    /// Change the timezone events are grouped in, the next poll fetches the new window
+    pub fn set_timezone(&self, timezone: Option<Tz>) {
+        *self.timezone.lock().unwrap() = timezone;
+        self.group_events();
+    }
+
+    /// Change the agenda horizon, the next poll fetches the new window
+    pub fn set_agenda_days(&self, days: i64) {
+        *self.agenda_days.lock().unwrap() = days;
+        self.group_events();
+    }
+}
+
+/// Accounts to poll and the window to fetch, see `Calendars::poll_request`
+pub struct PollRequest {
+    accounts: Arc<tokio::sync::Mutex<Vec<CalenderAccount>>>,
+    agenda_days: i64,
+    timezone: Option<Tz>,
+    generation: u64,
+}
+
+/// Events and sync state of every account after a poll
+pub struct PolledEvents {
+    generation: u64,
+    /// Events of each enabled account and whether they are fresh
+    results: Vec<(Vec<(EventSource, Event, Option<LeadTimes>)>, bool)>,
+    calendar_lists: HashMap<String, Vec<CalendarListEntry>>,
+    sync_status: HashMap<String, AccountSyncStatus>,
+}
+
+impl PollRequest {
+    /// Fetch events of every enabled account, only the account list stays locked
+    pub async fn fetch(self) -> PolledEvents {
+        let accounts = self.accounts.lock().await;
+        let agenda_days = self.agenda_days;
+        let timezone = self.timezone;
+        let results =
+            futures::future::join_all(accounts.iter().filter(|account| !account.is_diabled()).map(
+                |account| async {
+                    println!("Account to poll: {}", account.is_diabled());
+                    let email = account.email();
+                    let use_google_reminders =
+                        account.preferences.lock().await.use_google_reminders;
+                    let (events, fresh) = account.poll_calendar_events(agenda_days, timezone).await;
+                    let events = events
+                        .into_iter()
+                        .map(|(calendar_id, event)| {
+                            let reminder_lead_times = if use_google_reminders {
+                                account.reminder_lead_times(&calendar_id, &event)
+                            } else {
+                                None
+                            };
+                            let source = EventSource {
+                                account: email.clone(),
+                                calendar_id,
+                                response_status: response_status(&event, &email),
+                            };
+                            (source, event, reminder_lead_times)
+                        })
+                        .collect::<Vec<(EventSource, Event, Option<LeadTimes>)>>();
+                    (events, fresh)
+                },
+            ))
+            .await;
+
+        PolledEvents {
+            generation: self.generation,
+            results,
+            calendar_lists: accounts
+                .iter()
+                .map(|account| (account.email(), account.calendar_list.clone()))
+                .collect(),
+            sync_status: accounts
+                .iter()
+                .map(|account| (account.email(), account.sync_status()))
+                .collect(),
+        }
+    }
+}
+
+/// Response of `email` to the event invitation, the creator of an event or a
+/// calendar owner without an attendee entry has implicitly accepted it
+fn response_status(event: &Event, email: &str) -> String {
+    event
+        .attendees
+        .iter()
+        .find(|attendee| attendee.email == email)
+        .map_or(String::from("accepted"), |attendee| {
+            attendee.response_status.clone()
+        })
+}
+
+/// Identity of a meeting across accounts, its iCalendar uid and start time
+fn event_key(event: &Event) -> (String, Option<DateTime<Utc>>) {
+    let uid = if event.i_cal_uid.is_empty() {
+        event.id.clone()
+    } else {
+        event.i_cal_uid.clone()
+    };
+    (uid, event.start.clone().map(parse_event_datetime))
+}
+
+/// Calendar ids of each account, to tell whether calendar lists changed
+fn calendar_ids(calendar_lists: &HashMap<String, Vec<CalendarListEntry>>) -> Vec<(String, String)> {
+    let mut ids = calendar_lists
+        .iter()
+        .flat_map(|(email, calendars)| {
+            calendars
+                .iter()
+                .map(move |calendar| (email.clone(), calendar.id.clone()))
+        })
+        .collect::<Vec<(String, String)>>();
+    ids.sort();
+    ids
+}
+
+/// Ids and etags of a set of events, changes whenever any event is added, removed or updated
+fn fingerprint(events: &[Event]) -> Vec<(String, String)> {
+    let mut fingerprint = events
+        .iter()
+        .map(|event| (event.id.clone(), event.etag.clone()))
+        .collect::<Vec<(String, String)>>();
+    fingerprint.sort();
+    fingerprint
+}
+
+fn popup_minutes(reminders: &[google_calendar::types::EventReminder]) -> Vec<i64> {
+    reminders
+        .iter()
+        .filter(|reminder| reminder.method == "popup")
+        .map(|reminder| reminder.minutes)
+        .collect()
+}
+
+const CALENDAR_API_URL: &str = "https://www.googleapis.com/calendar/v3";
+/// Page size of calendar and event lists, the largest the calendar list accepts
+const MAX_RESULTS: &str = "250";
+/// Pages followed before a list is cut short
+const MAX_PAGES: usize = 40;
+/// Wait after the first failed sync of an account, doubled on every failure
+const BACKOFF_BASE_SECONDS: i64 = 60;
+const BACKOFF_MAX_SECONDS: i64 = 30 * 60;
+
+/// Every page of the account's calendar list
+async fn list_calendars(client: &Client) -> Result<Vec<CalendarListEntry>, ClientError> {
+    let mut calendars: Vec<CalendarListEntry> = vec![];
+    let mut page_token = String::new();
+    for page in 1..=MAX_PAGES {
+        let mut url = Url::parse(CALENDAR_API_URL).unwrap();
+        url.path_segments_mut()
+            .unwrap()
+            .extend(&["users", "me", "calendarList"]);
+        url.query_pairs_mut()
+            .append_pair("minAccessRole", "freeBusyReader")
+            .append_pair("maxResults", MAX_RESULTS);
+        if !page_token.is_empty() {
+            url.query_pairs_mut().append_pair("pageToken", &page_token);
+        }
+
+        let response = client
+            .get::<google_calendar::types::CalendarList>(url.as_str(), None)
+            .await?;
+        let body = response.body;
+        calendars.extend(body.items);
+
+        if body.next_page_token.is_empty() {
+            if page > 1 {
+                println!("Listed {} calendars in {} pages", calendars.len(), page);
+            }
+            return Ok(calendars);
+        }
+        page_token = body.next_page_token;
+    }
+
+    println!(
+        "Calendar list truncated at {} pages, {} calendars",
+        MAX_PAGES,
+        calendars.len()
+    );
+    Ok(calendars)
+}
+
+/// Incremental sync state of a calendar
+#[derive(Default)]
+struct CalendarSync {
+    /// `nextSyncToken` of the last sync, only valid for the same time window
+    sync_token: String,
+    window: (String, String),
+    /// Every event of the calendar known from the last syncs, keyed by event id
+    events: HashMap<String, Event>,
+    /// `nextPageToken` of a listing cut at `MAX_PAGES`, the next sync continues from it
+    page_token: String,
+}
+
+/// Where a calendar's event listing stopped
+enum ListEnd {
+    /// Last page reached, with the sync token Google sends on it
+    Synced(String),
+    /// Cut at `MAX_PAGES`, with the token of the page to continue from
+    Truncated(String),
+}
+
+/// Events overlapping the `window` of RFC 3339 start and end times
+fn events_in_window<'a>(
+    events: impl Iterator<Item = &'a Event>,
+    window: &(String, String),
+) -> Vec<Event> {
+    let window_start =
+        DateTime::parse_from_rfc3339(&window.0).map(|start| start.with_timezone(&Utc));
+    let window_end = DateTime::parse_from_rfc3339(&window.1).map(|end| end.with_timezone(&Utc));
+    events
+        .filter(|event| {
+            let start = event.start.clone().map(parse_event_datetime);
+            let end = event.end.clone().map(parse_event_datetime);
+            match (start, end, &window_start, &window_end) {
+                (Some(start), Some(end), Ok(window_start), Ok(window_end)) => {
+                    end > *window_start && start < *window_end
+                }
+                _ => true,
+            }
+        })
+        .cloned()
+        .collect()
+}
+
+/// Failed syncs in a row and when the account may be polled again
+#[derive(Debug, Default)]
+struct Backoff {
+    failures: u32,
+    retry_at: Option<DateTime<Utc>>,
+    /// Delay asked by Google with a `Retry-After` header on the last failure
+    retry_after: Option<chrono::Duration>,
+}
+
+impl Backoff {
+    /// Record a failed sync, returns when the account may be polled again
+    fn failed(&mut self) -> DateTime<Utc> {
+        self.failures += 1;
+        let exponential = BACKOFF_BASE_SECONDS * 2_i64.pow((self.failures - 1).min(10));
+        let delay = match self.retry_after.take() {
+            Some(retry_after) => retry_after.clamp(
+                chrono::Duration::zero(),
+                chrono::Duration::seconds(BACKOFF_MAX_SECONDS),
+            ),
+            None => chrono::Duration::seconds(exponential.min(BACKOFF_MAX_SECONDS)),
+        };
+        let retry_at = Utc::now() + delay;
+        self.retry_at = Some(retry_at);
+        retry_at
+    }
+}
+
+/// Delay asked by a 429 or 503 response, in seconds or as an HTTP date
+fn retry_after(err: &ClientError) -> Option<chrono::Duration> {
+    let headers = match err {
+        ClientError::HttpError {
+            status, headers, ..
+        } if *status == StatusCode::TOO_MANY_REQUESTS
+            || *status == StatusCode::SERVICE_UNAVAILABLE =>
+        {
+            headers
+        }
+        _ => return None,
+    };
+    let value = headers.get(RETRY_AFTER)?.to_str().ok()?;
+    match value.trim().parse::<i64>() {
+        Ok(seconds) => Some(chrono::Duration::seconds(seconds)),
+        Err(_) => DateTime::parse_from_rfc2822(value)
+            .ok()
+            .map(|date| date.with_timezone(&Utc) - Utc::now()),
+    }
 }
 
 pub struct CalenderAccount {
     token: Arc<Mutex<GoogleAuthToken>>,
-    calendar_list: Vec<google_calendar::types::CalendarListEntry>,
-    events: Events,
+    calendar_list: Vec<CalendarListEntry>,
+    calendar_sync: Mutex<HashMap<String, CalendarSync>>,
+    backoff: Mutex<Backoff>,
+    /// Events of the last successful sync, used while the account is failing
+    last_events: Mutex<Vec<(String, Event)>>,
+    last_synced_at: Mutex<Option<DateTime<Utc>>>,
+    last_error: Mutex<Option<String>>,
     client: Client,
     #[allow(dead_code)]
     event_groups: EventGroups,
@@ -350,7 +1124,7 @@ impl CalenderAccount {
                     now.subsec_nanos(),
                 )
                 .unwrap_or(DateTime::default());
-                let expiry_date = with_local_timezone(expiry_date);
+                let expiry_date = with_local_timezone(expiry_date, None);
                 println!("New Token expiry date - {:?}", &expiry_date);
                 token.expires_at = Some(expiry_date.timestamp());
 
@@ -372,27 +1146,24 @@ impl CalenderAccount {
         };
 
         // todo: pull user accounts update (email, etc)
-        let calendar_list = client.calendar_list();
-        let response = calendar_list
-            .list(20, MinAccessRole::FreeBusyReader, "", false, false)
-            .await;
-
-        let calendar_list = if response.is_ok() {
-            let list = response.unwrap().body;
-            println!("CalendarListEntry {:?}", list.len());
-            list
-        } else {
-            println!(
-                "Error listing calendar {account_email} {:?}",
-                response.err()
-            );
-            vec![]
+        let calendar_list = match list_calendars(&client).await {
+            Ok(list) => {
+                println!("CalendarListEntry {:?}", list.len());
+                list
+            }
+            Err(err) => {
+                println!("Error listing calendar {account_email} {:?}", err);
+                vec![]
+            }
         };
 
-        let events = Events::new(client.clone());
         CalenderAccount {
-            events,
             calendar_list,
+            calendar_sync: Mutex::new(HashMap::new()),
+            backoff: Mutex::new(Backoff::default()),
+            last_events: Mutex::new(vec![]),
+            last_synced_at: Mutex::new(None),
+            last_error: Mutex::new(None),
             preferences: tokio::sync::Mutex::new(preferences),
             token: Arc::new(Mutex::new(token)),
             client: client.to_owned(),
@@ -410,33 +1181,87 @@ impl CalenderAccount {
         false
     }
 
-    pub async fn get_calendar_events(&self) -> Vec<Event> {
+    pub fn email(&self) -> String {
+        self.token
+            .lock()
+            .unwrap()
+            .user
+            .as_ref()
+            .map_or(String::new(), |user| user.email.clone())
+    }
+
+    /// Events of the account and whether they are fresh, the last good events are
+    /// returned while the account is backing off or when the sync fails
+    pub async fn poll_calendar_events(
+        &self,
+        agenda_days: i64,
+        timezone: Option<Tz>,
+    ) -> (Vec<(String, Event)>, bool) {
+        let email = self.email();
+        let retry_at = self.backoff.lock().unwrap().retry_at;
+        if let Some(retry_at) = retry_at.filter(|retry_at| Utc::now() < *retry_at) {
+            println!("Backing off {} until {:?}", &email, retry_at);
+            return (self.last_events.lock().unwrap().clone(), false);
+        }
+
+        match self.get_calendar_events(agenda_days, timezone).await {
+            Ok(events) => {
+                *self.backoff.lock().unwrap() = Backoff::default();
+                *self.last_events.lock().unwrap() = events.clone();
+                *self.last_synced_at.lock().unwrap() = Some(Utc::now());
+                *self.last_error.lock().unwrap() = None;
+                (events, true)
+            }
+            Err(err) => {
+                *self.last_error.lock().unwrap() = Some(err.clone());
+                let retry_at = self.backoff.lock().unwrap().failed();
+                println!(
+                    "Poll events error {}: {}, retrying at {:?}",
+                    &email, &err, retry_at
+                );
+                (self.last_events.lock().unwrap().clone(), false)
+            }
+        }
+    }
+
+    /// Events of every visible calendar, paired with the id of the calendar they belong to,
+    /// a calendar that can't be fetched keeps its last good events. Fails when the token
+    /// can't be refreshed or no calendar can be fetched
+    pub async fn get_calendar_events(
+        &self,
+        agenda_days: i64,
+        timezone: Option<Tz>,
+    ) -> Result<Vec<(String, Event)>, String> {
         // println!("Is token expired for {}", self.token.lock().unwrap().clone().user.unwrap().email);
         let account_email = self.token.lock().unwrap().clone().user.unwrap().email;
         if self.is_token_expired().await {
             println!("Token expired for account: {}", &account_email);
             if let Err(err) = self.client.refresh_access_token().await {
                 println!("Refresh token Error: {} {:?}", &account_email, err);
-                return vec![];
+                return Err(err.to_string());
             }
         }
 
-        let time_min = chrono::offset::Local::now()
+        let time_min = local_now(timezone)
             .with_hour(0)
             .unwrap()
             .with_minute(0)
             .unwrap()
             .with_second(0)
+            .unwrap()
+            .with_nanosecond(0)
             .unwrap();
 
-        let time_max = chrono::offset::Local::now()
-            .checked_add_days(chrono::naive::Days::new(3))
+        let time_max = local_now(timezone)
+            .checked_add_days(chrono::naive::Days::new(agenda_days as u64))
             .unwrap()
             .with_hour(0)
             .unwrap()
             .with_minute(0)
             .unwrap()
             .with_second(0)
+            .unwrap()
+            .with_nanosecond(0)
             .unwrap();
 
         // println!(
@@ -445,90 +1270,232 @@ impl CalenderAccount {
         //     time_max.to_rfc3339()
         // );
         // let account_email = self.token.lock().unwrap().clone().user.unwrap().email;
+        let window = (time_min.to_rfc3339(), time_max.to_rfc3339());
         let preferences = self.preferences.lock().await;
-        let events = futures::future::join_all(
+        let visible_calendars = self
+            .calendar_list
+            .iter()
+            .filter(|calendar| !preferences.hidden_calendars.contains(&calendar.id))
+            .collect::<Vec<&CalendarListEntry>>();
+        let events = futures::future::join_all(visible_calendars.iter().map(|entry| async {
+            match self.sync_calendar(&entry.id, &window).await {
+                Ok(body) => {
+                    // println!("Fetch events success: {}: {}", &entry.id, body.len());
+                    let events = body
+                        .iter()
+                        .filter_map(|event| {
+                            let is_creator = {
+                                let creator = &event.creator;
+                                if let Some(creator) = creator {
+                                    creator.email == account_email
+                                } else {
+                                    false
+                                }
+                            };
+
+                            if is_creator {
+                                return Some((entry.id.clone(), event.to_owned()));
+                            }
+
+                            let is_user_attendee = event.attendees.iter().find(|attendee| {
+                                attendee.email
+                                    == self.token.lock().unwrap().clone().user.unwrap().email
+                            });
+
+                            let declined = is_user_attendee
+                                .map_or(false, |attendee| attendee.response_status == "declined");
+                            if declined {
+                                return None;
+                            }
+
+                            if is_user_attendee.is_some() {
+                                Some((entry.id.clone(), event.to_owned()))
+                            } else {
+                                None
+                            }
+                        })
+                        .collect::<Vec<(String, Event)>>();
+                    Ok(events)
+                }
+                Err(err) => {
+                    println!("Fetch event Error: {} - {}", &entry.id, err);
+                    Err(err)
+                }
+            }
+        }))
+        .await;
+
+        if !events.is_empty() && events.iter().all(|result| result.is_err()) {
+            let errors = events
+                .into_iter()
+                .filter_map(Result::err)
+                .collect::<Vec<String>>();
+            return Err(errors.join(", "));
+        }
+
+        let last_events = self.last_events.lock().unwrap().clone();
+        let events = events
+            .into_iter()
+            .zip(visible_calendars.iter())
+            .flat_map(|(result, entry)| match result {
+                Ok(events) => events,
+                Err(_) => last_events
+                    .iter()
+                    .filter(|(calendar_id, _)| calendar_id == &entry.id)
+                    .cloned()
+                    .collect(),
+            })
+            .collect();
+        Ok(events)
+    }
+
+    /// Events of a calendar within `window`, fetched incrementally with the calendar's
+    /// sync token, a full sync is done the first time, when the window moves or when
+    /// Google expired the token. A listing cut at `MAX_PAGES` continues on the next sync
+    async fn sync_calendar(
+        &self,
+        calendar_id: &str,
+        window: &(String, String),
+    ) -> Result<Vec<Event>, String> {
+        let (mut sync_token, mut page_token) = self
+            .calendar_sync
+            .lock()
+            .unwrap()
+            .get(calendar_id)
+            .filter(|sync| &sync.window == window)
+            .map_or((String::new(), String::new()), |sync| {
+                (sync.sync_token.clone(), sync.page_token.clone())
+            });
+
+        let listed = match self
+            .list_events(calendar_id, window, &sync_token, &page_token)
+            .await
+        {
+            Err(ClientError::HttpError { status, .. })
+                if status == StatusCode::GONE
+                    && !(sync_token.is_empty() && page_token.is_empty()) =>
+            {
+                println!("Sync token expired for {}, full resync", calendar_id);
+                sync_token.clear();
+                page_token.clear();
+                self.list_events(calendar_id, window, "", "").await
+            }
+            listed => listed,
+        };
+        let (changes, list_end) = listed.map_err(|err| self.sync_error(err))?;
+
+        let mut calendar_sync = self.calendar_sync.lock().unwrap();
+        let sync = calendar_sync.entry(calendar_id.to_string()).or_default();
+        if sync_token.is_empty() && page_token.is_empty() {
+            // a full sync from its first page replaces every known event
+            *sync = CalendarSync {
+                window: window.clone(),
+                ..CalendarSync::default()
+            };
+        }
+        for event in changes.into_iter() {
+            if event.status == "cancelled" {
+                println!("Event cancelled: {} {}", calendar_id, &event.id);
+                sync.events.remove(&event.id);
+            } else {
+                sync.events.insert(event.id.clone(), event);
+            }
+        }
+        match list_end {
+            ListEnd::Synced(next_sync_token) => {
+                sync.sync_token = next_sync_token;
+                sync.page_token.clear();
+            }
+            ListEnd::Truncated(next_page_token) => sync.page_token = next_page_token,
+        }
+        Ok(events_in_window(sync.events.values(), window))
+    }
+
+    /// Keep the `Retry-After` delay of a rate limited request for the account backoff
+    fn sync_error(&self, err: ClientError) -> String {
+        if let Some(delay) = retry_after(&err) {
+            self.backoff.lock().unwrap().retry_after = Some(delay);
+        }
+        err.to_string()
+    }
+
+    /// Pages of a calendar's event list, the changes since `sync_token` or every event
+    /// of `window` when it is empty, starting at `page_token` and up to `MAX_PAGES`
+    async fn list_events(
+        &self,
+        calendar_id: &str,
+        window: &(String, String),
+        sync_token: &str,
+        page_token: &str,
+    ) -> Result<(Vec<Event>, ListEnd), ClientError> {
+        let mut params = vec![("singleEvents", "true")];
+        if sync_token.is_empty() {
+            params.push(("timeMin", window.0.as_str()));
+            params.push(("timeMax", window.1.as_str()));
+        } else {
+            params.push(("syncToken", sync_token));
+        }
+
+        let mut events: Vec<Event> = vec![];
+        let mut page_token = page_token.to_string();
+        for page in 1..=MAX_PAGES {
+            let mut url = Url::parse(CALENDAR_API_URL).unwrap();
+            url.path_segments_mut()
+                .unwrap()
+                .extend(&["calendars", calendar_id, "events"]);
+            url.query_pairs_mut()
+                .extend_pairs(params.iter())
+                .append_pair("maxResults", MAX_RESULTS);
+            if !page_token.is_empty() {
+                url.query_pairs_mut().append_pair("pageToken", &page_token);
+            }
+
+            let response = self
+                .client
+                .get::<google_calendar::types::Events>(url.as_str(), None)
+                .await?;
+            let body = response.body;
+            events.extend(body.items);
+
+            if body.next_page_token.is_empty() {
+                if page > 1 {
+                    println!(
+                        "Listed {} events of {} in {} pages",
+                        events.len(),
+                        calendar_id,
+                        page
+                    );
+                }
+                return Ok((events, ListEnd::Synced(body.next_sync_token)));
+            }
+            page_token = body.next_page_token;
+        }
+
+        println!(
+            "Event list of {} stopped at {} pages, {} events, continuing on the next sync",
+            calendar_id,
+            MAX_PAGES,
+            events.len()
+        );
+        Ok((events, ListEnd::Truncated(page_token)))
+    }
+
+    /// Lead times from the event's popup reminders, or the calendar's default reminders
+    /// when the event uses them
+    pub fn reminder_lead_times(&self, calendar_id: &str, event: &Event) -> Option<LeadTimes> {
+        let reminders = event.reminders.as_ref()?;
+        let minutes = if reminders.use_default {
             self.calendar_list
                 .iter()
-                .filter(|calendar| !preferences.hidden_calendars.contains(&calendar.id))
-                .map(|entry| async {
-                    let response = self
-                        .events
-                        .list(
-                            &entry.id,
-                            "",
-                            0,
-                            0,
-                            google_calendar::types::OrderBy::Noop,
-                            "",
-                            &[],
-                            "",
-                            &[],
-                            false,
-                            false,
-                            true,
-                            &time_max.to_rfc3339(),
-                            &time_min.to_rfc3339(),
-                            "",
-                            "",
-                        )
-                        .await;
-                    // let response = response.unwrap();
-                    if let Ok(response) = response {
-                        if response.status.is_success() {
-                            let body = response.body;
-                            // println!("Fetch events success: {}: {} {}", &entry.id, response.status.to_string(), body.len());
-                            body.iter()
-                                .filter_map(|event| {
-                                    let is_creator = {
-                                        let creator = &event.creator;
-                                        if let Some(creator) = creator {
-                                            creator.email == account_email
-                                        } else {
-                                            false
-                                        }
-                                    };
-
-                                    if is_creator {
-                                        return Some(event.to_owned());
-                                    }
-
-                                    let is_user_attendee =
-                                        event.attendees.iter().find(|attendee| {
-                                            attendee.email
-                                                == self
-                                                    .token
-                                                    .lock()
-                                                    .unwrap()
-                                                    .clone()
-                                                    .user
-                                                    .unwrap()
-                                                    .email
-                                        });
-
-                                    if is_user_attendee.is_some() {
-                                        Some(event.to_owned())
-                                    } else {
-                                        None
-                                    }
-                                })
-                                .collect::<Vec<Event>>()
-                        } else {
-                            println!("Fetch events error: {}", response.status.to_string());
-                            vec![]
-                        }
-                    } else {
-                        println!("Fetch event Error: {} - {:?}", &entry.id, response.err());
-                        vec![]
-                    }
-                }),
-        )
-        .await;
+                .find(|calendar| calendar.id == calendar_id)
+                .map_or(vec![], |calendar| {
+                    popup_minutes(&calendar.default_reminders)
+                })
+        } else {
+            popup_minutes(&reminders.overrides)
+        };
 
-        events
-            .iter()
-            .flatten()
-            .map(|e| e.to_owned())
-            .collect::<Vec<Event>>()
+        LeadTimes::from_reminders(&minutes)
     }
 
     pub async fn is_token_expired(&self) -> bool {
@@ -565,7 +1532,7 @@ impl CalenderAccount {
             now.subsec_nanos(),
         )
         .unwrap_or(DateTime::default());
-        let expiry_date = with_local_timezone(expiry_date);
+        let expiry_date = with_local_timezone(expiry_date, None);
 
         let prev_token = self.token.lock().unwrap().clone();
         *self.token.lock().unwrap() = GoogleAuthToken {
@@ -609,6 +1576,35 @@ impl CalenderAccount {
         !self.is_diabled()
     }
 
+    pub fn sync_status(&self) -> AccountSyncStatus {
+        let last_synced_at = *self.last_synced_at.lock().unwrap();
+        let last_error = self.last_error.lock().unwrap().clone();
+        let state = if self.is_diabled() {
+            SyncState::Disabled
+        } else if last_error.is_some() {
+            SyncState::Failing
+        } else if last_synced_at.is_some() {
+            SyncState::Synced
+        } else {
+            SyncState::Pending
+        };
+
+        AccountSyncStatus {
+            account: self.email(),
+            state,
+            last_synced_at,
+            last_error,
+            retry_at: self.backoff.lock().unwrap().retry_at,
+            token_expires_at: self
+                .token
+                .lock()
+                .unwrap()
+                .expires_at
+                .and_then(|expires_at| DateTime::from_timestamp(expires_at, 0)),
+            calendar_count: self.calendar_list.len(),
+        }
+    }
+
     pub async fn set_preferences(&self, account_preference: AccountPreference) {
         *self.preferences.lock().await = account_preference;
     }
@@ -0,0 +1,75 @@
+use crate::types::{EventSource, LeadTimes};
+use chrono::{DateTime, Utc};
+use google_calendar::types::{CalendarListEntry, Event};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Last synced events and calendar lists, used until the first poll succeeds
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct EventCache {
+    #[serde(skip)]
+    storage_path: String,
+    pub events: Vec<Event>,
+    /// Every account and calendar an event was fetched from, keyed by event id
+    pub sources: HashMap<String, Vec<EventSource>>,
+    /// Lead times taken from Google reminders, keyed by event id
+    pub reminder_lead_times: HashMap<String, LeadTimes>,
+    /// Calendar lists keyed by account email
+    pub calendar_lists: HashMap<String, Vec<CalendarListEntry>>,
+    pub synced_at: Option<DateTime<Utc>>,
+}
+
+impl EventCache {
+    pub fn load_from_file(app_path: PathBuf) -> Self {
+        let storage_path: PathBuf = app_path.join("events_cache.json");
+        let cache = match fs::read_to_string(&storage_path) {
+            Ok(cache) => serde_json::from_str::<EventCache>(&cache).unwrap_or_else(|err| {
+                println!("Error parsing events cache {:?}", err);
+                EventCache::default()
+            }),
+            Err(_) => EventCache::default(),
+        };
+        println!(
+            "Events cache: {} events synced at {:?}",
+            cache.events.len(),
+            &cache.synced_at
+        );
+
+        EventCache {
+            storage_path: storage_path
+                .to_str()
+                .map_or(String::from(""), |value| value.to_string()),
+            ..cache
+        }
+    }
+
+    /// Forget every cached event and delete the cache file
+    pub fn clear(&mut self) -> Result<(), String> {
+        *self = EventCache {
+            storage_path: self.storage_path.clone(),
+            ..EventCache::default()
+        };
+        if !Path::new(&self.storage_path).is_file() {
+            return Ok(());
+        }
+
+        fs::remove_file(&self.storage_path).map_err(|err| {
+            println!("Error removing events cache {:?}", err);
+            err.to_string()
+        })
+    }
+
+    pub fn save_state(&self) -> Result<(), String> {
+        if self.storage_path.is_empty() {
+            return Ok(());
+        }
+
+        let bytes = serde_json::to_vec(self).map_err(|err| err.to_string())?;
+        fs::write(&self.storage_path, bytes).map_err(|err| {
+            println!("Error saving events cache {:?}", err);
+            err.to_string()
+        })
+    }
+}
@@ -0,0 +1,90 @@
+use crate::utils::parse_event_datetime;
+use chrono::{DateTime, Utc};
+use google_calendar::types::Event;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::PathBuf;
+
+/// Maximum number of records kept in the history file
+const MAX_RECORDS: usize = 500;
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AlertOutcome {
+    Fired,
+    Dismissed,
+    Snoozed,
+    /// Alert was due while the app could not show it (e.g. machine asleep)
+    Missed,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct AlertRecord {
+    pub event_id: String,
+    pub summary: String,
+    pub start: Option<DateTime<Utc>>,
+    pub outcome: AlertOutcome,
+    pub timestamp: DateTime<Utc>,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct AlertHistory {
+    #[serde(skip)]
+    storage_path: String,
+    records: Vec<AlertRecord>,
+}
+
+impl AlertHistory {
+    pub fn load_from_file(app_path: PathBuf) -> Self {
+        let storage_path: PathBuf = app_path.join("alert_history.json");
+        let history = match fs::read_to_string(&storage_path) {
+            Ok(history) => serde_json::from_str::<AlertHistory>(&history).unwrap_or_else(|err| {
+                println!("Error parsing alert history {:?}", err);
+                AlertHistory::default()
+            }),
+            Err(_) => AlertHistory::default(),
+        };
+
+        AlertHistory {
+            storage_path: storage_path
+                .to_str()
+                .map_or(String::from(""), |value| value.to_string()),
+            ..history
+        }
+    }
+
+    pub fn save_state(&self) -> Result<(), String> {
+        if self.storage_path.is_empty() {
+            return Ok(());
+        }
+
+        let bytes = serde_json::to_vec(self).map_err(|err| err.to_string())?;
+        fs::write(&self.storage_path, bytes).map_err(|err| {
+            println!("Error saving alert history {:?}", err);
+            err.to_string()
+        })
+    }
+
+    pub fn record(&mut self, event: &Event, outcome: AlertOutcome) {
+        println!("Alert history: {:?} {}", &outcome, &event.summary);
+        self.records.push(AlertRecord {
+            event_id: event.id.clone(),
+            summary: event.summary.clone(),
+            start: event.start.clone().map(parse_event_datetime),
+            outcome,
+            timestamp: Utc::now(),
+        });
+
+        if self.records.len() > MAX_RECORDS {
+            let overflow = self.records.len() - MAX_RECORDS;
+            self.records.drain(..overflow);
+        }
+
+        let _ = self.save_state();
+    }
+
+    /// Records from newest to oldest
+    pub fn list(&self) -> Vec<AlertRecord> {
+        self.records.iter().rev().cloned().collect()
+    }
+}
@@ -1,4 +1,7 @@
 pub mod utils;
 pub mod autostart;
 pub mod account;
-pub mod types;
\ No newline at end of file
+pub mod types;
+pub mod history;
+pub mod cache;
+pub mod locale;
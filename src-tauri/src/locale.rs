@@ -0,0 +1,279 @@
+use crate::types::Language;
+use chrono::{Datelike, NaiveDate};
+
+/// Singular and plural of minute, hour and day
+type Units = [(&'static str, &'static str); 3];
+
+/// User facing strings of the tray and notifications, `{}` placeholders are
+/// filled in order by `fill`
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Phrase {
+    AllDay,
+    DayOf,
+    Ending,
+    Upcoming,
+    Tomorrow,
+    LaterThisWeek,
+    JoinMeeting,
+    Offline,
+    Today,
+    StartsIn,
+    StartsNow,
+    WrapUp,
+    Ends,
+    EndsThenNext,
+    EndsInShort,
+    StartsInShort,
+    CountdownMinutes,
+    CountdownHours,
+    CountdownHoursMinutes,
+    OverlapsWith,
+    OverlapsWithFrom,
+    BackToBackWith,
+    BackToBackWithFrom,
+    Ok,
+    SnoozeMinutes,
+    EventStarted,
+    EventStarts,
+    EventEnded,
+    JoinGoogleMeet,
+    JoinZoom,
+    ShowApp,
+    AddAccount,
+    QuitApp,
+}
+
+impl Language {
+    pub fn text(self, phrase: Phrase) -> &'static str {
+        match self {
+            Language::En => match phrase {
+                Phrase::AllDay => "All day",
+                Phrase::DayOf => "Day {}/{}",
+                Phrase::Ending => "Ending {}",
+                Phrase::Upcoming => "Upcoming {}",
+                Phrase::Tomorrow => "Tomorrow",
+                Phrase::LaterThisWeek => "Later this week",
+                Phrase::JoinMeeting => "Join meeting",
+                Phrase::Offline => "Offline, synced {}",
+                Phrase::Today => "{} today",
+                Phrase::StartsIn => "{} starts in {} minutes",
+                Phrase::StartsNow => "{} starts now!",
+                Phrase::WrapUp => "Wrap up {}",
+                Phrase::Ends => "{} ends {}",
+                Phrase::EndsThenNext => "{} ends {}, {} starts right after at {}",
+                Phrase::EndsInShort => "Ends in {}",
+                Phrase::StartsInShort => "{} in {}",
+                Phrase::CountdownMinutes => "{}m",
+                Phrase::CountdownHours => "{}h",
+                Phrase::CountdownHoursMinutes => "{}h {}m",
+                Phrase::OverlapsWith => "Overlaps with {}",
+                Phrase::OverlapsWithFrom => "Overlaps with {} from {}",
+                Phrase::BackToBackWith => "Back-to-back with {}",
+                Phrase::BackToBackWithFrom => "Back-to-back with {} from {}",
+                Phrase::Ok => "Ok",
+                Phrase::SnoozeMinutes => "Snooze {} min",
+                Phrase::EventStarted => "This event started {}",
+                Phrase::EventStarts => "This event will start {}",
+                Phrase::EventEnded => "This event ended {}",
+                Phrase::JoinGoogleMeet => "Join Google meet meeting",
+                Phrase::JoinZoom => "Join Zoom meeting",
+                Phrase::ShowApp => "Notor App",
+                Phrase::AddAccount => "Add new account",
+                Phrase::QuitApp => "Quit Notor app completely",
+            },
+            Language::Fr => match phrase {
+                Phrase::AllDay => "Toute la journée",
+                Phrase::DayOf => "Jour {}/{}",
+                Phrase::Ending => "Se termine {}",
+                Phrase::Upcoming => "À venir {}",
+                Phrase::Tomorrow => "Demain",
+                Phrase::LaterThisWeek => "Plus tard cette semaine",
+                Phrase::JoinMeeting => "Rejoindre la réunion",
+                Phrase::Offline => "Hors ligne, synchronisé {}",
+                Phrase::Today => "{} aujourd'hui",
+                Phrase::StartsIn => "{} commence dans {} minutes",
+                Phrase::StartsNow => "{} commence maintenant !",
+                Phrase::WrapUp => "Conclure {}",
+                Phrase::Ends => "{} se termine {}",
+                Phrase::EndsThenNext => "{} se termine {}, {} commence juste après à {}",
+                Phrase::EndsInShort => "Fin dans {}",
+                Phrase::StartsInShort => "{} dans {}",
+                Phrase::CountdownMinutes => "{} min",
+                Phrase::CountdownHours => "{} h",
+                Phrase::CountdownHoursMinutes => "{} h {}",
+                Phrase::OverlapsWith => "Chevauche {}",
+                Phrase::OverlapsWithFrom => "Chevauche {} de {}",
+                Phrase::BackToBackWith => "Enchaîné avec {}",
+                Phrase::BackToBackWithFrom => "Enchaîné avec {} de {}",
+                Phrase::Ok => "Ok",
+                Phrase::SnoozeMinutes => "Répéter dans {} min",
+                Phrase::EventStarted => "Cet événement a commencé {}",
+                Phrase::EventStarts => "Cet événement commence {}",
+                Phrase::EventEnded => "Cet événement s'est terminé {}",
+                Phrase::JoinGoogleMeet => "Rejoindre la réunion Google Meet",
+                Phrase::JoinZoom => "Rejoindre la réunion Zoom",
+                Phrase::ShowApp => "App Notor",
+                Phrase::AddAccount => "Ajouter un compte",
+                Phrase::QuitApp => "Quitter complètement Notor",
+            },
+            Language::De => match phrase {
+                Phrase::AllDay => "Ganztägig",
+                Phrase::DayOf => "Tag {}/{}",
+                Phrase::Ending => "Endet {}",
+                Phrase::Upcoming => "Demnächst {}",
+                Phrase::Tomorrow => "Morgen",
+                Phrase::LaterThisWeek => "Später diese Woche",
+                Phrase::JoinMeeting => "Meeting beitreten",
+                Phrase::Offline => "Offline, synchronisiert {}",
+                Phrase::Today => "{} heute",
+                Phrase::StartsIn => "{} beginnt in {} Minuten",
+                Phrase::StartsNow => "{} beginnt jetzt!",
+                Phrase::WrapUp => "{} abschließen",
+                Phrase::Ends => "{} endet {}",
+                Phrase::EndsThenNext => "{} endet {}, {} beginnt direkt danach um {}",
+                Phrase::EndsInShort => "Endet in {}",
+                Phrase::StartsInShort => "{} in {}",
+                Phrase::CountdownMinutes => "{} Min.",
+                Phrase::CountdownHours => "{} Std.",
+                Phrase::CountdownHoursMinutes => "{} Std. {} Min.",
+                Phrase::OverlapsWith => "Überschneidet sich mit {}",
+                Phrase::OverlapsWithFrom => "Überschneidet sich mit {} von {}",
+                Phrase::BackToBackWith => "Direkt vor oder nach {}",
+                Phrase::BackToBackWithFrom => "Direkt vor oder nach {} von {}",
+                Phrase::Ok => "Ok",
+                Phrase::SnoozeMinutes => "{} Min. schlummern",
+                Phrase::EventStarted => "Dieser Termin hat {} begonnen",
+                Phrase::EventStarts => "Dieser Termin beginnt {}",
+                Phrase::EventEnded => "Dieser Termin endete {}",
+                Phrase::JoinGoogleMeet => "Google Meet beitreten",
+                Phrase::JoinZoom => "Zoom-Meeting beitreten",
+                Phrase::ShowApp => "Notor App",
+                Phrase::AddAccount => "Neues Konto hinzufügen",
+                Phrase::QuitApp => "Notor vollständig beenden",
+            },
+            Language::Es => match phrase {
+                Phrase::AllDay => "Todo el día",
+                Phrase::DayOf => "Día {}/{}",
+                Phrase::Ending => "Termina {}",
+                Phrase::Upcoming => "Próximo {}",
+                Phrase::Tomorrow => "Mañana",
+                Phrase::LaterThisWeek => "Más tarde esta semana",
+                Phrase::JoinMeeting => "Unirse a la reunión",
+                Phrase::Offline => "Sin conexión, sincronizado {}",
+                Phrase::Today => "{} hoy",
+                Phrase::StartsIn => "{} empieza en {} minutos",
+                Phrase::StartsNow => "¡{} empieza ahora!",
+                Phrase::WrapUp => "Concluir {}",
+                Phrase::Ends => "{} termina {}",
+                Phrase::EndsThenNext => "{} termina {}, {} empieza justo después a las {}",
+                Phrase::EndsInShort => "Termina en {}",
+                Phrase::StartsInShort => "{} en {}",
+                Phrase::CountdownMinutes => "{} min",
+                Phrase::CountdownHours => "{} h",
+                Phrase::CountdownHoursMinutes => "{} h {} min",
+                Phrase::OverlapsWith => "Se superpone con {}",
+                Phrase::OverlapsWithFrom => "Se superpone con {} de {}",
+                Phrase::BackToBackWith => "Seguido de {}",
+                Phrase::BackToBackWithFrom => "Seguido de {} de {}",
+                Phrase::Ok => "Aceptar",
+                Phrase::SnoozeMinutes => "Posponer {} min",
+                Phrase::EventStarted => "Este evento empezó {}",
+                Phrase::EventStarts => "Este evento empieza {}",
+                Phrase::EventEnded => "Este evento terminó {}",
+                Phrase::JoinGoogleMeet => "Unirse a Google Meet",
+                Phrase::JoinZoom => "Unirse a la reunión de Zoom",
+                Phrase::ShowApp => "App Notor",
+                Phrase::AddAccount => "Añadir cuenta",
+                Phrase::QuitApp => "Salir completamente de Notor",
+            },
+        }
+    }
+
+    /// Short weekday names, Monday first
+    fn weekdays(self) -> [&'static str; 7] {
+        match self {
+            Language::En => ["Mon", "Tue", "Wed", "Thu", "Fri", "Sat", "Sun"],
+            Language::Fr => ["lun.", "mar.", "mer.", "jeu.", "ven.", "sam.", "dim."],
+            Language::De => ["Mo", "Di", "Mi", "Do", "Fr", "Sa", "So"],
+            Language::Es => ["lun", "mar", "mié", "jue", "vie", "sáb", "dom"],
+        }
+    }
+
+    /// Future and past templates, then singular and plural of minute, hour and day
+    fn relative_words(self) -> (&'static str, &'static str, Units) {
+        match self {
+            Language::En => (
+                "in {}",
+                "{} ago",
+                [("minute", "minutes"), ("hour", "hours"), ("day", "days")],
+            ),
+            Language::Fr => (
+                "dans {}",
+                "il y a {}",
+                [
+                    ("minute", "minutes"),
+                    ("heure", "heures"),
+                    ("jour", "jours"),
+                ],
+            ),
+            Language::De => (
+                "in {}",
+                "vor {}",
+                [
+                    ("Minute", "Minuten"),
+                    ("Stunde", "Stunden"),
+                    ("Tag", "Tagen"),
+                ],
+            ),
+            Language::Es => (
+                "en {}",
+                "hace {}",
+                [("minuto", "minutos"), ("hora", "horas"), ("día", "días")],
+            ),
+        }
+    }
+
+    fn now(self) -> &'static str {
+        match self {
+            Language::En => "now",
+            Language::Fr => "maintenant",
+            Language::De => "jetzt",
+            Language::Es => "ahora",
+        }
+    }
+}
+
+/// Replace each `{}` of `template` with the next argument
+pub fn fill(template: &str, args: &[&str]) -> String {
+    let mut parts = template.split("{}");
+    let mut filled = parts.next().unwrap_or_default().to_string();
+    for (idx, part) in parts.enumerate() {
+        filled.push_str(args.get(idx).copied().unwrap_or_default());
+        filled.push_str(part);
+    }
+    filled
+}
+
+pub fn weekday(language: Language, date: NaiveDate) -> String {
+    language.weekdays()[date.weekday().num_days_from_monday() as usize].to_string()
+}
+
+/// "in 5 minutes" or "2 hours ago" in `language`, rounded to the largest unit
+pub fn relative_time(language: Language, duration: chrono::Duration) -> String {
+    let (future, past, units) = language.relative_words();
+    let seconds = duration.num_seconds();
+    let minutes = (seconds.abs() + 30) / 60;
+    if minutes == 0 {
+        return language.now().to_string();
+    }
+
+    let (count, (singular, plural)) = if minutes < 60 {
+        (minutes, units[0])
+    } else if minutes < 60 * 24 {
+        ((minutes + 30) / 60, units[1])
+    } else {
+        ((minutes + 60 * 12) / (60 * 24), units[2])
+    };
+    let amount = format!("{} {}", count, if count == 1 { singular } else { plural });
+    fill(if seconds > 0 { future } else { past }, &[&amount])
+}
@@ -3,10 +3,23 @@
 
 mod server;
 
-use crate::server::{open_alert_window, open_auth_window};
+use crate::server::{
+    alert_queue_payload, drop_alert_queue, join_event_meeting, open_alert_window, open_auth_window,
+    poll_calendars, publish_sync_status, reconcile_pending_events, remove_queued_alert, reschedule,
+    show_event_details, snooze_event_alert,
+};
 use app::autostart;
-use app::types::{AppState, GoogleAuthToken, Preferences};
-use app::utils::{get_date_time, get_human_readable_time, time_to_relative_format};
+use app::history::{AlertOutcome, AlertRecord};
+use app::locale::{fill, relative_time, Phrase};
+use app::types::{
+    AccountSyncStatus, AlertPayload, AllDayPolicy, AppState, EventClickAction, EventConflict,
+    GoogleAuthToken, InvitationPolicy, Language, LeadTimes, Preferences, TimeFormat,
+    TrayTitleFormat,
+};
+use app::utils::{
+    all_day_span, get_date_time, get_human_readable_time, is_all_day, join_url, later_day_label,
+    local_now, original_time_label, time_to_relative_format, tray_title, EventGroups,
+};
 use google_calendar::types::Event;
 use std::path::PathBuf;
 use std::{fs, io::Write, thread};
@@ -45,17 +58,66 @@ async fn schedule_events(window: Window, events: Vec<Event>) -> Result<(), Strin
             .unwrap()
             .insert(event.id.clone(), event.to_owned());
     }
+    reschedule(&window.app_handle());
     Ok(())
 }
 
+/// Dismiss one event of the alert queue, or every queued event when no id is given
 #[tauri::command]
-async fn dismiss_alert(window: Window) -> Result<(), String> {
-    println!("dismiss_alert Event {}", window.label());
-    if window.close().is_ok() {
-        Ok(())
-    } else {
-        Err("Error closing alert window".to_string())
+async fn dismiss_alert(window: Window, event_id: Option<String>) -> Result<(), String> {
+    println!("dismiss_alert Event {} {:?}", window.label(), &event_id);
+    let handle = window.app_handle();
+    let event_ids = match event_id {
+        Some(event_id) => vec![event_id],
+        None => handle
+            .state::<AppState>()
+            .alert_queue
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|event| event.id.clone())
+            .collect::<Vec<String>>(),
+    };
+
+    for event_id in event_ids.iter() {
+        if let Some(event) = remove_queued_alert(&handle, event_id).await {
+            handle
+                .state::<AppState>()
+                .alert_history
+                .lock()
+                .unwrap()
+                .record(&event, AlertOutcome::Dismissed);
+        }
     }
+    Ok(())
+}
+
+#[tauri::command]
+async fn snooze_alert(window: Window, event_id: String, minutes: i64) -> Result<(), String> {
+    println!("snooze_alert Event {}: {} mins", &event_id, minutes);
+    snooze_event_alert(&window.app_handle(), &event_id, minutes).await
+}
+
+#[tauri::command]
+async fn get_alert_queue(window: Window) -> Result<Vec<AlertPayload>, String> {
+    Ok(alert_queue_payload(&window.app_handle()).await)
+}
+
+#[tauri::command]
+async fn join_meeting(window: Window, event_id: String) -> Result<(), String> {
+    join_event_meeting(&window.app_handle(), &event_id).await
+}
+
+#[tauri::command]
+async fn list_alert_history(window: Window) -> Result<Vec<AlertRecord>, String> {
+    let history = window
+        .app_handle()
+        .state::<AppState>()
+        .alert_history
+        .lock()
+        .unwrap()
+        .list();
+    Ok(history)
 }
 
 #[tauri::command]
@@ -71,6 +133,13 @@ async fn logout(window: Window) {
     };
 
     let _ = std::fs::remove_file(data_path);
+    let state = handle.state::<AppState>();
+    state.calendars.lock().await.sign_out().await;
+    state.pending_events.lock().unwrap().clear();
+    state.fired_stages.lock().unwrap().clear();
+    state.snoozed_alerts.lock().unwrap().clear();
+    publish_sync_status(&handle).await;
+    reschedule(&handle);
 
     let _ = open_auth_window(&handle);
 
@@ -79,121 +148,228 @@ async fn logout(window: Window) {
 
 fn event_to_relative_time_string(
     event: &Event,
+    conflicts: &[EventConflict],
+    preferences: &Preferences,
     menu: &mut Vec<CustomMenuItem>,
 ) -> Vec<CustomMenuItem> {
-    let time = get_date_time(event);
-    let time_str = get_human_readable_time(time);
+    let language = preferences.language();
+    let timezone = preferences.timezone();
+    let time_str = if is_all_day(event) {
+        let today = local_now(timezone).date_naive();
+        match all_day_span(event, today) {
+            Some((day, days)) if days > 1 && day >= 1 => fill(
+                language.text(Phrase::DayOf),
+                &[&day.to_string(), &days.to_string()],
+            ),
+            _ => language.text(Phrase::AllDay).to_string(),
+        }
+    } else {
+        get_human_readable_time(get_date_time(event, timezone), preferences.time_format())
+    };
+    let time_str = match later_day_label(event, language, timezone) {
+        Some(day) => format!("{} {}", day, time_str),
+        None => time_str,
+    };
+    let time_str = match original_time_label(event, preferences.time_format(), timezone) {
+        Some(original) => format!("{} ({})", time_str, original),
+        None => time_str,
+    };
+    let warnings = conflicts
+        .iter()
+        .filter_map(|conflict| conflict.warning_for(&event.id, language))
+        .collect::<Vec<String>>();
+    let marker = if warnings.is_empty() { "" } else { "  ⚠️" };
     menu.push(CustomMenuItem::new(
-        &event.id,
-        format!("{} {}  {}", "   ", time_str, &event.summary),
+        format!("event:{}", &event.id),
+        format!("{} {}  {}{}", "   ", time_str, &event.summary, marker),
     ));
+    if join_url(event).is_some() {
+        menu.push(CustomMenuItem::new(
+            format!("join:{}", &event.id),
+            format!("{}     {}", "   ", language.text(Phrase::JoinMeeting)),
+        ));
+    }
+    for (idx, warning) in warnings.iter().enumerate() {
+        menu.push(
+            CustomMenuItem::new(
+                format!("{}_conflict_{}", &event.id, idx),
+                format!("{}     {}", "   ", warning),
+            )
+            .disabled(),
+        );
+    }
     menu.to_owned()
 }
 
 pub async fn update_try_app(app: &AppHandle) -> Result<(), String> {
-    let events = app
-        .state::<AppState>()
-        .calendars
-        .lock()
-        .await
-        .event_groups
-        .lock()
-        .unwrap()
-        .clone();
+    let (events, conflicts) = {
+        let state = app.state::<AppState>();
+        let calendars = state.calendars.lock().await;
+        let events = calendars.event_groups.lock().unwrap().clone();
+        (events, calendars.conflicts())
+    };
+    let preferences = app.state::<AppState>().preferences.lock().await.get_state();
+    let title = tray_title(&events, &preferences, chrono::Utc::now());
+    let language = preferences.language();
 
     // println!("Now Groups {:?}", events.now.iter().map(|g| &g.summary).collect::<Vec<&String>>());
     // println!("Upcoming Groups {:?}", events.upcoming.iter().map(|g| &g.summary).collect::<Vec<&String>>());
     // println!("Tomorrow Groups {:?}", events.tomorrow.iter().map(|g| &g.summary).collect::<Vec<&String>>());
-    if events.now.is_empty() && events.upcoming.is_empty() && events.tomorrow.is_empty() {
-        return Ok(());
+    let mut event_items: Vec<CustomMenuItem> = vec![];
+
+    if let (true, Some(synced_at)) = (events.stale, events.synced_at) {
+        let synced = relative_time(language, synced_at - chrono::Utc::now());
+        let offline =
+            CustomMenuItem::new("offline", fill(language.text(Phrase::Offline), &[&synced]))
+                .native_image(tauri::NativeImage::StatusUnavailable)
+                .disabled();
+        event_items.push(offline);
     }
 
-    let mut system_tray_menu = SystemTrayMenu::new();
+    if !events.all_day.is_empty() {
+        let all_day = CustomMenuItem::new("all_day", language.text(Phrase::AllDay))
+            .native_image(tauri::NativeImage::StatusNone)
+            .disabled();
+        event_items.push(all_day);
 
-    let mut ongoing_event_items: Vec<CustomMenuItem> = vec![];
-    if !events.now.is_empty() {
-        let end_time = time_to_relative_format(events.now.first().unwrap().clone().end.unwrap());
+        for event in events.all_day.iter() {
+            event_items =
+                event_to_relative_time_string(event, &conflicts, &preferences, &mut event_items);
+        }
+    }
 
-        let ongoing = CustomMenuItem::new("ongoing", format!("Ending {}", end_time))
-            .native_image(tauri::NativeImage::StatusAvailable)
-            .disabled();
+    if !events.now.is_empty() {
+        let end_time =
+            time_to_relative_format(events.now.first().unwrap().clone().end.unwrap(), language);
 
-        ongoing_event_items.push(ongoing);
+        let ongoing =
+            CustomMenuItem::new("ongoing", fill(language.text(Phrase::Ending), &[&end_time]))
+                .native_image(tauri::NativeImage::StatusAvailable)
+                .disabled();
+        event_items.push(ongoing);
 
         for event in events.now.iter() {
-            ongoing_event_items = event_to_relative_time_string(event, &mut ongoing_event_items);
-        }
-
-        for menu in ongoing_event_items.iter() {
-            system_tray_menu = system_tray_menu.add_item(menu.to_owned());
+            event_items =
+                event_to_relative_time_string(event, &conflicts, &preferences, &mut event_items);
         }
     }
 
-    let mut upcoming_event_items: Vec<CustomMenuItem> = vec![];
     if !events.upcoming.is_empty() {
-        let start_time =
-            time_to_relative_format(events.upcoming.first().unwrap().clone().start.unwrap());
-        let upcoming = CustomMenuItem::new("upcoming", format!("Upcoming {}", start_time))
-            .native_image(tauri::NativeImage::StatusPartiallyAvailable)
-            .disabled();
-        upcoming_event_items.push(upcoming);
+        let start_time = time_to_relative_format(
+            events.upcoming.first().unwrap().clone().start.unwrap(),
+            language,
+        );
+        let upcoming = CustomMenuItem::new(
+            "upcoming",
+            fill(language.text(Phrase::Upcoming), &[&start_time]),
+        )
+        .native_image(tauri::NativeImage::StatusPartiallyAvailable)
+        .disabled();
+        event_items.push(upcoming);
 
         for event in events.upcoming.iter() {
-            upcoming_event_items = event_to_relative_time_string(event, &mut upcoming_event_items);
-        }
-
-        for menu in upcoming_event_items.iter() {
-            system_tray_menu = system_tray_menu.add_item(menu.to_owned());
+            event_items =
+                event_to_relative_time_string(event, &conflicts, &preferences, &mut event_items);
         }
     }
 
-    let mut tomorrow_event_items: Vec<CustomMenuItem> = vec![];
     if !events.tomorrow.is_empty() {
-        let tomorrow = CustomMenuItem::new("tomorrow", "Tomorrow")
+        let tomorrow = CustomMenuItem::new("tomorrow", language.text(Phrase::Tomorrow))
             .native_image(tauri::NativeImage::StatusUnavailable)
             .disabled();
-        tomorrow_event_items.push(tomorrow);
+        event_items.push(tomorrow);
 
         for event in events.tomorrow.iter() {
-            tomorrow_event_items = event_to_relative_time_string(event, &mut tomorrow_event_items);
+            event_items =
+                event_to_relative_time_string(event, &conflicts, &preferences, &mut event_items);
         }
+    }
+
+    if !events.later.is_empty() {
+        let later = CustomMenuItem::new("later", language.text(Phrase::LaterThisWeek))
+            .native_image(tauri::NativeImage::StatusUnavailable)
+            .disabled();
+        event_items.push(later);
 
-        for menu in tomorrow_event_items.iter() {
-            system_tray_menu = system_tray_menu.add_item(menu.to_owned());
+        for event in events.later.iter() {
+            event_items =
+                event_to_relative_time_string(event, &conflicts, &preferences, &mut event_items);
         }
     }
 
-    let quit = CustomMenuItem::new("quit", "Quit Notor app completely");
-    let settings = CustomMenuItem::new("settings", "Add new account");
+    let tray = app
+        .tray_handle_by_id("events_tray")
+        .ok_or_else(|| String::from("Events tray not found"))?;
 
-    system_tray_menu = system_tray_menu
-        .add_native_item(SystemTrayMenuItem::Separator)
-        .add_item(CustomMenuItem::new("show_app", "Notor App"))
-        .add_item(settings)
-        .add_native_item(SystemTrayMenuItem::Separator)
-        .add_item(quit);
+    let items = event_items
+        .iter()
+        .map(|item| (item.id_str.clone(), item.title.clone()))
+        .collect::<Vec<(String, String)>>();
 
-    let _ = SystemTray::new()
-        .with_id("events_tray")
-        .with_title("Event in 2mins")
-        .with_menu(system_tray_menu)
-        .build(app);
+    let state = app.state::<AppState>();
+    let mut rendered = state.rendered_tray.lock().unwrap();
+
+    if rendered.title != title {
+        tray.set_title(&title).map_err(|err| err.to_string())?;
+        rendered.title = title;
+    }
+
+    let same_layout = rendered.language == language
+        && rendered.items.len() == items.len()
+        && rendered
+            .items
+            .iter()
+            .zip(items.iter())
+            .all(|((rendered_id, _), (id, _))| rendered_id == id);
+
+    if same_layout {
+        // Only relative times or labels changed, update those items in place
+        for ((_, rendered_title), (id, title)) in rendered.items.iter().zip(items.iter()) {
+            if rendered_title != title {
+                if let Some(item) = tray.try_get_item(id) {
+                    item.set_title(title).map_err(|err| err.to_string())?;
+                }
+            }
+        }
+    } else {
+        let mut system_tray_menu = SystemTrayMenu::new();
+        for item in event_items.into_iter() {
+            system_tray_menu = system_tray_menu.add_item(item);
+        }
+        if !items.is_empty() {
+            system_tray_menu = system_tray_menu.add_native_item(SystemTrayMenuItem::Separator);
+        }
+        tray.set_menu(tray_menu_footer(system_tray_menu, language))
+            .map_err(|err| err.to_string())?;
+    }
+    rendered.items = items;
+    rendered.language = language;
 
     Ok(())
 }
 
-fn build_tray_app(app_handle: &tauri::App) -> Result<(), ()> {
-    let quit = CustomMenuItem::new("quit", "Quit Notor app completely             ❌");
-    let settings = CustomMenuItem::new("settings", "Add new account");
-    let system_tray_menu = SystemTrayMenu::new()
-        .add_item(CustomMenuItem::new("show_app", "Notor App"))
+/// Items shown at the bottom of the tray menu regardless of events
+fn tray_menu_footer(system_tray_menu: SystemTrayMenu, language: Language) -> SystemTrayMenu {
+    let quit = CustomMenuItem::new("quit", language.text(Phrase::QuitApp));
+    let settings = CustomMenuItem::new("settings", language.text(Phrase::AddAccount));
+
+    system_tray_menu
+        .add_item(CustomMenuItem::new(
+            "show_app",
+            language.text(Phrase::ShowApp),
+        ))
         .add_item(settings)
         .add_native_item(SystemTrayMenuItem::Separator)
-        .add_item(quit);
+        .add_item(quit)
+}
+
+fn build_tray_app(app_handle: &tauri::App) -> Result<(), ()> {
+    // labelled in the default language until the first update knows the preference
+    let system_tray_menu = tray_menu_footer(SystemTrayMenu::new(), Language::default());
 
     let _ = SystemTray::new()
         .with_id("events_tray")
-        .with_title("Event in 2mins")
+        .with_title("")
         .with_menu(system_tray_menu)
         .build(app_handle);
     Ok(())
@@ -215,16 +391,17 @@ async fn list_accounts(window: Window) -> Result<Vec<GoogleAuthToken>, String> {
 #[tauri::command]
 async fn remove_account(window: Window, email: String) -> Result<(), String> {
     let handle = window.app_handle();
-    let state = handle.state::<AppState>();
-    let pref = state.preferences.lock().await;
     let _ = handle
         .state::<AppState>()
         .calendars
         .lock()
         .await
-        .remove_account(email, &pref)
+        .remove_account(email)
         .await;
+    publish_sync_status(&handle).await;
     save_app_state(window.app_handle()).await;
+    reconcile_pending_events(&handle).await;
+    reschedule(&handle);
     Ok(())
 }
 
@@ -239,7 +416,10 @@ async fn disable_account(window: Window, email: String) -> Result<(), String> {
         .await
         .disable_account(email)
         .await;
+    publish_sync_status(&window.app_handle()).await;
     save_app_state(window.app_handle()).await;
+    reconcile_pending_events(&window.app_handle()).await;
+    reschedule(&window.app_handle());
     Ok(())
 }
 
@@ -254,7 +434,10 @@ async fn enable_account(window: Window, email: String) -> Result<(), String> {
         .await
         .enable_account(email)
         .await;
+    publish_sync_status(&window.app_handle()).await;
     save_app_state(window.app_handle()).await;
+    reconcile_pending_events(&window.app_handle()).await;
+    reschedule(&window.app_handle());
     Ok(())
 }
 
@@ -284,6 +467,8 @@ async fn hide_calendar(window: Window, email: String, calendar_id: String) -> Re
         .await
         .set_preferences(email, &preferences)
         .await;
+    reconcile_pending_events(&handle).await;
+    reschedule(&handle);
     Ok(())
 }
 
@@ -313,9 +498,267 @@ async fn show_calendar(window: Window, email: String, calendar_id: String) -> Re
         .await
         .set_preferences(email, &preferences)
         .await;
+    reconcile_pending_events(&handle).await;
+    reschedule(&handle);
+    Ok(())
+}
+
+#[tauri::command]
+async fn set_lead_times(
+    window: Window,
+    email: String,
+    calendar_id: Option<String>,
+    lead_times: LeadTimes,
+) -> Result<(), String> {
+    println!(
+        "Set lead times: {} - {:?} {:?}",
+        &email, &calendar_id, &lead_times
+    );
+    let handle = window.app_handle();
+    handle
+        .state::<AppState>()
+        .preferences
+        .lock()
+        .await
+        .set_lead_times(email, calendar_id, lead_times)
+        .await?;
+    reschedule(&handle);
     Ok(())
 }
 
+#[tauri::command]
+async fn set_use_google_reminders(
+    window: Window,
+    email: String,
+    value: bool,
+) -> Result<(), String> {
+    println!("Use google reminders: {} - {}", &email, value);
+    let handle = window.app_handle();
+    handle
+        .state::<AppState>()
+        .preferences
+        .lock()
+        .await
+        .set_use_google_reminders(email.clone(), value)
+        .await?;
+
+    let preferences = handle
+        .state::<AppState>()
+        .preferences
+        .lock()
+        .await
+        .get_state();
+
+    handle
+        .state::<AppState>()
+        .calendars
+        .lock()
+        .await
+        .set_use_google_reminders(email, &preferences)
+        .await;
+    reschedule(&handle);
+    Ok(())
+}
+
+#[tauri::command]
+async fn set_wrap_up_minutes(window: Window, minutes: Option<i64>) -> Result<(), String> {
+    println!("Set wrap up minutes: {:?}", &minutes);
+    let handle = window.app_handle();
+    handle
+        .state::<AppState>()
+        .preferences
+        .lock()
+        .await
+        .set_wrap_up_minutes(minutes)
+        .await;
+    reschedule(&handle);
+    Ok(())
+}
+
+#[tauri::command]
+async fn get_notify_only_meetings(window: Window) -> Result<bool, String> {
+    let value = window
+        .app_handle()
+        .state::<AppState>()
+        .preferences
+        .lock()
+        .await
+        .notify_only_meetings();
+    Ok(value)
+}
+
+#[tauri::command]
+async fn set_notify_only_meetings(window: Window, value: bool) -> Result<(), String> {
+    println!("Notify only meetings: {}", value);
+    let handle = window.app_handle();
+    handle
+        .state::<AppState>()
+        .preferences
+        .lock()
+        .await
+        .set_notify_only_meetings(value)
+        .await;
+    reschedule(&handle);
+    Ok(())
+}
+
+#[tauri::command]
+async fn set_pending_invitations(window: Window, policy: InvitationPolicy) -> Result<(), String> {
+    println!("Pending invitations policy: {:?}", &policy);
+    let handle = window.app_handle();
+    handle
+        .state::<AppState>()
+        .preferences
+        .lock()
+        .await
+        .set_pending_invitations(policy)
+        .await;
+    reschedule(&handle);
+    Ok(())
+}
+
+#[tauri::command]
+async fn set_all_day_events(
+    window: Window,
+    policy: AllDayPolicy,
+    hour: Option<u32>,
+) -> Result<(), String> {
+    println!("All day events policy: {:?} {:?}", &policy, &hour);
+    let handle = window.app_handle();
+    handle
+        .state::<AppState>()
+        .preferences
+        .lock()
+        .await
+        .set_all_day_events(policy, hour)
+        .await?;
+    reschedule(&handle);
+    Ok(())
+}
+
+#[tauri::command]
+async fn set_tray_title(
+    window: Window,
+    format: TrayTitleFormat,
+    max_length: Option<usize>,
+) -> Result<(), String> {
+    println!("Tray title: {:?} {:?}", &format, &max_length);
+    let handle = window.app_handle();
+    handle
+        .state::<AppState>()
+        .preferences
+        .lock()
+        .await
+        .set_tray_title(format, max_length)
+        .await;
+    reschedule(&handle);
+    Ok(())
+}
+
+#[tauri::command]
+async fn set_event_click_action(window: Window, action: EventClickAction) -> Result<(), String> {
+    println!("Event click action: {:?}", &action);
+    window
+        .app_handle()
+        .state::<AppState>()
+        .preferences
+        .lock()
+        .await
+        .set_event_click_action(action)
+        .await;
+    Ok(())
+}
+
+#[tauri::command]
+async fn set_locale(
+    window: Window,
+    time_format: TimeFormat,
+    language: Language,
+) -> Result<(), String> {
+    println!("Locale: {:?} {:?}", &time_format, &language);
+    let handle = window.app_handle();
+    handle
+        .state::<AppState>()
+        .preferences
+        .lock()
+        .await
+        .set_locale(time_format, language)
+        .await;
+    reschedule(&handle);
+    Ok(())
+}
+
+#[tauri::command]
+async fn set_timezone(window: Window, timezone: Option<String>) -> Result<(), String> {
+    println!("Timezone override: {:?}", &timezone);
+    let handle = window.app_handle();
+    let state = handle.state::<AppState>();
+    let timezone = {
+        let mut preferences = state.preferences.lock().await;
+        preferences.set_timezone(timezone).await?;
+        preferences.timezone()
+    };
+    state.calendars.lock().await.set_timezone(timezone);
+    reschedule(&handle);
+    Ok(())
+}
+
+#[tauri::command]
+async fn set_agenda_days(window: Window, days: i64) -> Result<(), String> {
+    println!("Agenda days: {}", days);
+    let handle = window.app_handle();
+    let state = handle.state::<AppState>();
+    let days = {
+        let mut preferences = state.preferences.lock().await;
+        preferences.set_agenda_days(days).await;
+        preferences.agenda_days()
+    };
+    state.calendars.lock().await.set_agenda_days(days);
+    poll_calendars(&handle).await;
+    reconcile_pending_events(&handle).await;
+    reschedule(&handle);
+    Ok(())
+}
+
+#[tauri::command]
+async fn get_event_groups(window: Window) -> Result<EventGroups, String> {
+    let handle = window.app_handle();
+    let state = handle.state::<AppState>();
+    let groups = state
+        .calendars
+        .lock()
+        .await
+        .event_groups
+        .lock()
+        .unwrap()
+        .clone();
+    Ok(groups)
+}
+
+#[tauri::command]
+async fn get_sync_status(window: Window) -> Result<Vec<AccountSyncStatus>, String> {
+    let sync_status = window
+        .app_handle()
+        .state::<AppState>()
+        .calendars
+        .lock()
+        .await
+        .sync_status();
+    Ok(sync_status)
+}
+
+#[tauri::command]
+async fn get_conflicts(window: Window) -> Result<Vec<EventConflict>, String> {
+    let conflicts = window
+        .app_handle()
+        .state::<AppState>()
+        .calendars
+        .lock()
+        .await
+        .conflicts();
+    Ok(conflicts)
+}
+
 #[tauri::command]
 async fn get_preferences(window: Window) -> Result<Preferences, String> {
     println!("Get Preferences");
@@ -398,6 +841,10 @@ async fn main() {
             logout,
             show_alert,
             dismiss_alert,
+            snooze_alert,
+            get_alert_queue,
+            join_meeting,
+            list_alert_history,
             schedule_events,
             list_accounts,
             remove_account,
@@ -405,6 +852,21 @@ async fn main() {
             enable_account,
             hide_calendar,
             show_calendar,
+            set_lead_times,
+            set_use_google_reminders,
+            set_wrap_up_minutes,
+            get_notify_only_meetings,
+            set_notify_only_meetings,
+            set_pending_invitations,
+            set_all_day_events,
+            set_tray_title,
+            set_event_click_action,
+            set_agenda_days,
+            set_locale,
+            set_timezone,
+            get_event_groups,
+            get_sync_status,
+            get_conflicts,
             get_preferences
         ])
         .on_system_tray_event(move |app, event| match event {
@@ -441,6 +903,7 @@ async fn main() {
             }
             SystemTrayEvent::MenuItemClick { id, .. } => {
                 if id.as_str() == "quit" {
+                    drop_alert_queue(app);
                     std::process::exit(0);
                 } else if id.as_str() == "show_app" {
                     println!("show app");
@@ -453,6 +916,22 @@ async fn main() {
                     if result.is_err() {
                         // log error
                     }
+                } else if let Some(event_id) = id.strip_prefix("join:") {
+                    let app = app.clone();
+                    let event_id = event_id.to_string();
+                    tauri::async_runtime::spawn(async move {
+                        if let Err(err) = join_event_meeting(&app, &event_id).await {
+                            println!("Join meeting error: {}", err);
+                        }
+                    });
+                } else if let Some(event_id) = id.strip_prefix("event:") {
+                    let app = app.clone();
+                    let event_id = event_id.to_string();
+                    tauri::async_runtime::spawn(async move {
+                        if let Err(err) = show_event_details(&app, &event_id).await {
+                            println!("Show event error: {}", err);
+                        }
+                    });
                 }
             }
             _ => {}
@@ -462,6 +941,11 @@ async fn main() {
                 event.window().hide().unwrap();
                 api.prevent_close();
             }
+            tauri::WindowEvent::Destroyed => {
+                if event.window().label() == "alert" {
+                    drop_alert_queue(&event.window().app_handle());
+                }
+            }
             tauri::WindowEvent::Focused(false) => {
                 if event.window().label() == "main" {
                     event.window().hide().unwrap();
@@ -6,7 +6,7 @@ use std::{fs, io::Write, path::PathBuf};
 use tauri::Manager;
 use tokio;
 
-use crate::server::{utils::e500, TauriAppState};
+use crate::server::{publish_sync_status, utils::e500, TauriAppState};
 use app::types::{AppState, GoogleAuthToken};
 use app::utils::with_local_timezone;
 
@@ -174,6 +174,7 @@ pub async fn google_login(
                 .unwrap();
             println!("Google Login UnLocked---------+++++++");
         }
+        publish_sync_status(&app_state.app).await;
 
         let auth_tokens = app_state
             .app
@@ -1,7 +1,8 @@
 use actix_cors::Cors;
 use actix_web::{http::header, middleware, web, App, HttpServer};
 use app::types::{
-    AppCredentials, AppState, GoogleAuthToken, Preferences, StateToken, TauriAppState,
+    AlertLabels, AlertPayload, AlertStage, AppCredentials, AppState, EventClickAction,
+    GoogleAuthToken, Preferences, SnoozedAlert, StateToken, TauriAppState,
 };
 use std::io::Write;
 use std::sync::Arc;
@@ -9,8 +10,14 @@ use std::{fs, path::PathBuf};
 
 use crate::update_try_app;
 use app::account::Calendars;
-use app::utils::with_local_timezone;
-use chrono::{NaiveTime, TimeZone};
+use app::cache::EventCache;
+use app::history::{AlertHistory, AlertOutcome};
+use app::locale::{fill, Phrase};
+use app::utils::{
+    event_time_label, get_date_time, get_human_readable_time, is_all_day, join_url,
+    next_back_to_back, original_time_label, parse_event_datetime, time_to_relative_format,
+};
+use chrono::{DateTime, Utc};
 use google_calendar::types::Event;
 use std::time::{Duration, SystemTime};
 use tauri::api::notification::{Notification, Sound};
@@ -48,7 +55,6 @@ pub async fn open_alert_window(app: &AppHandle, title: String) -> Result<(), Str
         auth_window.close().unwrap();
         // auth_window.
         // TODO: emit event to do a reload and refresh the current event displayed
-        // TODO: keep record of the missed alert
         tokio::time::sleep(Duration::from_secs(2)).await;
     }
 
@@ -82,6 +88,189 @@ pub async fn open_alert_window(app: &AppHandle, title: String) -> Result<(), Str
     Ok(())
 }
 
+/// Add an event to the alert queue and show it in the fullscreen alert window, the
+/// window is only opened once and lists every event that is currently due
+pub async fn show_event_alert(app: &AppHandle, event: &Event, outcome: AlertOutcome) {
+    let state = app.state::<AppState>();
+    {
+        let mut alert_queue = state.alert_queue.lock().unwrap();
+        if alert_queue.iter().any(|queued| queued.id == event.id) {
+            return;
+        }
+        alert_queue.push(event.clone());
+    }
+    state.alert_history.lock().unwrap().record(event, outcome);
+
+    let payload = alert_payload(app, event).await;
+    let window = app.get_window("main");
+    if window.is_some() {
+        window.unwrap().emit("alert", &payload).unwrap();
+    }
+
+    if app.get_window("alert").is_some() {
+        emit_alert_queue(app).await;
+    } else {
+        let _ = open_alert_window(app, event.summary.to_owned()).await;
+        emit_alert_queue(app).await;
+    }
+}
+
+async fn alert_payload(app: &AppHandle, event: &Event) -> AlertPayload {
+    let state = app.state::<AppState>();
+    let (time_format, language, timezone) = {
+        let preferences = state.preferences.lock().await;
+        (
+            preferences.time_format(),
+            preferences.language(),
+            preferences.timezone(),
+        )
+    };
+    let warnings = state
+        .calendars
+        .lock()
+        .await
+        .conflict_warnings(&event.id, language);
+    AlertPayload {
+        event: event.clone(),
+        warnings,
+        join_url: join_url(event),
+        time_label: event_time_label(event, time_format, language, timezone),
+        original_time_label: original_time_label(event, time_format, timezone),
+        labels: AlertLabels::new(language),
+    }
+}
+
+/// Events currently shown in the alert window
+pub async fn alert_queue_payload(app: &AppHandle) -> Vec<AlertPayload> {
+    let alert_queue = app.state::<AppState>().alert_queue.lock().unwrap().clone();
+    let mut payload = vec![];
+    for event in alert_queue.iter() {
+        payload.push(alert_payload(app, event).await);
+    }
+    payload
+}
+
+async fn emit_alert_queue(app: &AppHandle) {
+    let payload = alert_queue_payload(app).await;
+    let _ = app.emit_all("alerts", &payload);
+}
+
+/// Remove an event from the alert queue, closing the alert window once it is empty
+pub async fn remove_queued_alert(app: &AppHandle, event_id: &str) -> Option<Event> {
+    let (event, is_empty) = {
+        let state = app.state::<AppState>();
+        let mut alert_queue = state.alert_queue.lock().unwrap();
+        let position = alert_queue.iter().position(|event| event.id == event_id);
+        let event = position.map(|idx| alert_queue.remove(idx));
+        (event, alert_queue.is_empty())
+    };
+
+    if is_empty {
+        if let Some(alert_window) = app.get_window("alert") {
+            let _ = alert_window.close();
+        }
+    } else {
+        emit_alert_queue(app).await;
+    }
+    event
+}
+
+/// Empty the alert queue, recording its alerts as missed, when the alert window is
+/// closed or the app quits before they were dismissed
+pub fn drop_alert_queue(app: &AppHandle) {
+    let state = app.state::<AppState>();
+    let alert_queue = std::mem::take(&mut *state.alert_queue.lock().unwrap());
+    let mut alert_history = state.alert_history.lock().unwrap();
+    for event in alert_queue.iter() {
+        alert_history.record(event, AlertOutcome::Missed);
+    }
+}
+
+/// Look an event up in the synced events, the alert queue or the pending alerts
+async fn lookup_event(app: &AppHandle, event_id: &str) -> Result<Event, String> {
+    let state = app.state::<AppState>();
+    let event = state.calendars.lock().await.find_event(event_id);
+    event
+        .or_else(|| {
+            state
+                .alert_queue
+                .lock()
+                .unwrap()
+                .iter()
+                .find(|event| event.id == event_id)
+                .cloned()
+        })
+        .or_else(|| state.pending_events.lock().unwrap().get(event_id).cloned())
+        .ok_or(format!("Event {} not found", event_id))
+}
+
+/// Open the video call of an event in the default browser
+pub async fn join_event_meeting(app: &AppHandle, event_id: &str) -> Result<(), String> {
+    let event = lookup_event(app, event_id).await?;
+
+    let url = join_url(&event).ok_or(format!("No meeting link for {}", &event.summary))?;
+    println!("Join meeting {}: {}", &event.summary, &url);
+    tauri::api::shell::open(&app.shell_scope(), url, None).map_err(|err| err.to_string())
+}
+
+/// Show an event clicked in the tray, in the main window or in Google Calendar
+pub async fn show_event_details(app: &AppHandle, event_id: &str) -> Result<(), String> {
+    let event = lookup_event(app, event_id).await?;
+    let action = app
+        .state::<AppState>()
+        .preferences
+        .lock()
+        .await
+        .event_click_action();
+
+    if action == EventClickAction::OpenInCalendar && !event.html_link.is_empty() {
+        println!("Open event {}: {}", &event.summary, &event.html_link);
+        return tauri::api::shell::open(&app.shell_scope(), &event.html_link, None)
+            .map_err(|err| err.to_string());
+    }
+
+    let window = app
+        .get_window("main")
+        .ok_or(String::from("Main window not found"))?;
+    window.show().map_err(|err| err.to_string())?;
+    window.set_focus().map_err(|err| err.to_string())?;
+    let payload = alert_payload(app, &event).await;
+    window
+        .emit("show-event", payload)
+        .map_err(|err| err.to_string())
+}
+
+/// Longest delay an alert can be snoozed for
+const MAX_SNOOZE_MINUTES: i64 = 240;
+
+/// Take an event off the alert queue and show it again after `minutes`
+pub async fn snooze_event_alert(
+    app: &AppHandle,
+    event_id: &str,
+    minutes: i64,
+) -> Result<(), String> {
+    if !(1..=MAX_SNOOZE_MINUTES).contains(&minutes) {
+        return Err(format!("Invalid snooze delay {} minutes", minutes));
+    }
+    let event = remove_queued_alert(app, event_id)
+        .await
+        .ok_or(format!("No alert to snooze for event {}", event_id))?;
+    let until = chrono::Utc::now() + chrono::Duration::minutes(minutes);
+    println!("Snoozed {} until {:?}", &event.summary, &until);
+    app.state::<AppState>()
+        .alert_history
+        .lock()
+        .unwrap()
+        .record(&event, AlertOutcome::Snoozed);
+    app.state::<AppState>()
+        .snoozed_alerts
+        .lock()
+        .unwrap()
+        .insert(event.id.clone(), SnoozedAlert { event, until });
+    reschedule(app);
+    Ok(())
+}
+
 pub async fn get_app_config() -> Result<AppCredentials, reqwest::Error> {
     let api_url = if cfg!(debug_assertions) {
         "http://localhost:4876"
@@ -97,101 +286,437 @@ pub async fn get_app_config() -> Result<AppCredentials, reqwest::Error> {
     Ok(response)
 }
 
-pub async fn run_timer_until_stopped(handle: AppHandle) -> Result<(), anyhow::Error> {
-    loop {
-        let _ = {
-            handle
-                .state::<AppState>()
-                .calendars
-                .lock()
-                .await
-                .poll_events()
-                .await;
-        };
-
-        let state = &handle.state::<AppState>().pending_events;
-        let mut next_event: Option<Event> = None;
-        for (_, event) in state.lock().unwrap().iter() {
-            if event.start.is_none() {
-                continue;
-            }
-            let start = event.start.clone().unwrap();
+/// Interval between calendar syncs
+const SYNC_INTERVAL: Duration = Duration::from_secs(60);
+/// Longest the scheduler sleeps without a deadline, keeps the tray's relative times fresh
+const MAX_IDLE: Duration = Duration::from_secs(60);
 
-            let start_time = {
-                let time = if let Some(date_time) = start.date_time {
-                    with_local_timezone(date_time)
-                } else {
-                    let date = start.date.unwrap();
-                    let date_with_time = date.and_time(NaiveTime::default());
-                    with_local_timezone(
-                        chrono::Local
-                            .from_local_datetime(&date_with_time)
-                            .unwrap()
-                            .to_utc(),
-                    )
-                };
-                time
-            };
+/// Wake the alert scheduler so it recomputes its next deadline
+pub fn reschedule(app: &AppHandle) {
+    app.state::<AppState>().schedule_changed.notify_one();
+}
 
-            let now = with_local_timezone(chrono::Utc::now());
-            let diff = start_time.timestamp() - now.timestamp();
-            if diff.is_negative() {
-                // event has started, dispatch notification and exit
-                println!("Event has started {}", &event.summary);
+pub async fn run_timer_until_stopped(handle: AppHandle) -> Result<(), anyhow::Error> {
+    let sync_task = tokio::spawn(run_calendar_sync(handle.clone()));
+    let scheduler_task = tokio::spawn(run_alert_scheduler(handle));
+    tokio::select! {
+        _o = sync_task => report_exit("Calendar sync"),
+        _o = scheduler_task => report_exit("Alert scheduler"),
+    }
+    Ok(())
+}
 
-                next_event = Some(event.clone());
-                break;
-            } else {
-                // println!("Minutes left until {}: {:?} {}", &event.summary, diff / 60, diff);
-            }
+/// Send the account sync status to the UI when it changed since it was last sent
+pub async fn publish_sync_status(app: &AppHandle) {
+    let state = app.state::<AppState>();
+    let sync_status = state.calendars.lock().await.sync_status();
+    {
+        let mut published = state.sync_status.lock().unwrap();
+        if *published == sync_status {
+            return;
         }
+        *published = sync_status.clone();
+    }
+    let _ = app.emit_all("sync-status", &sync_status);
+}
+
+/// Fetch events without holding the calendars so alerts and the tray keep running,
+/// returns whether the event set changed
+pub async fn poll_calendars(handle: &AppHandle) -> bool {
+    let poll_request = handle
+        .state::<AppState>()
+        .calendars
+        .lock()
+        .await
+        .poll_request();
+    let polled = poll_request.fetch().await;
+    handle
+        .state::<AppState>()
+        .calendars
+        .lock()
+        .await
+        .apply_poll(polled)
+}
 
-        let upcoming_events = handle
+async fn run_calendar_sync(handle: AppHandle) {
+    loop {
+        handle
             .state::<AppState>()
             .calendars
             .lock()
             .await
-            .upcoming_events();
-        for event in upcoming_events.iter() {
-            handle
-                .state::<AppState>()
-                .pending_events
-                .lock()
-                .unwrap()
-                .insert(event.id.clone(), event.to_owned());
+            .mark_syncing();
+        publish_sync_status(&handle).await;
+
+        let changed = poll_calendars(&handle).await;
+        publish_sync_status(&handle).await;
+
+        let reconciled = reconcile_pending_events(&handle).await;
+        if changed || reconciled {
+            reschedule(&handle);
         }
+        println!("Calendar sync end {:?}", SystemTime::now());
+        tokio::time::sleep(SYNC_INTERVAL).await;
+    }
+}
 
-        if let Some(value) = next_event {
-            handle
-                .state::<AppState>()
-                .pending_events
-                .lock()
-                .unwrap()
-                .remove(&value.id);
-            let window = handle.get_window("main");
+async fn run_alert_scheduler(handle: AppHandle) {
+    let state = handle.state::<AppState>();
+    loop {
+        queue_upcoming_events(&handle).await;
+        dispatch_due_alerts(&handle).await;
+        dispatch_wrap_ups(&handle).await;
+        let _ = update_try_app(&handle).await;
+
+        let deadline = next_alert_deadline(&handle).await;
+        let sleep_for = deadline
+            .map_or(MAX_IDLE, |deadline| {
+                (deadline - chrono::Utc::now())
+                    .to_std()
+                    .unwrap_or(Duration::ZERO)
+            })
+            .min(MAX_IDLE);
+        println!(
+            "Next alert deadline {:?}, sleeping {:?}",
+            &deadline, &sleep_for
+        );
+
+        tokio::select! {
+            _ = tokio::time::sleep(sleep_for) => {},
+            _ = state.schedule_changed.notified() => {
+                println!("Alert scheduler woken by event change");
+            },
+        }
+    }
+}
+
+/// Update pending events from the freshly synced event set and drop the ones that were
+/// cancelled, moved out of the sync window or belong to a hidden calendar. Returns
+/// whether any pending event changed
+pub async fn reconcile_pending_events(handle: &AppHandle) -> bool {
+    let state = handle.state::<AppState>();
+    let calendars = state.calendars.lock().await;
+    let mut pending_events = state.pending_events.lock().unwrap();
+    let mut fired_stages = state.fired_stages.lock().unwrap();
+
+    let mut changed = false;
+    let mut stale_ids = vec![];
+    for (id, pending) in pending_events.iter_mut() {
+        // a missing event only means it is gone when its accounts synced
+        if !calendars.is_event_synced(id) {
             println!(
-                "================Alert Event : {} {:?}=========",
-                &value.summary,
-                &value.start.clone().unwrap()
+                "Skipping reconciliation of {}, its account failed",
+                &pending.summary
             );
-            println!("End time: {:?}", &value.start.clone().unwrap());
-            if window.is_some() {
-                window.unwrap().emit("alert", &value).unwrap();
+            continue;
+        }
+        match calendars.find_event(id) {
+            Some(event) => {
+                if event.etag == pending.etag {
+                    continue;
+                }
+                let start = event.start.clone().map(parse_event_datetime);
+                if start != pending.start.clone().map(parse_event_datetime) {
+                    // rescheduled, alert again at the new time
+                    println!("Pending event rescheduled {}", &event.summary);
+                    fired_stages.remove(id);
+                }
+                *pending = event;
+                changed = true;
             }
-            let _ = open_alert_window(&handle, value.summary.to_owned()).await;
-
-            Notification::new(&handle.config().tauri.bundle.identifier)
-                .title(value.summary.to_string())
-                .body(format!("{} starts now!", value.summary))
-                .sound(Sound::Default)
-                .show()
-                .unwrap();
+            None => stale_ids.push(id.clone()),
         }
+    }
 
-        let _ = update_try_app(&handle).await;
-        println!("Timer ticked end {:?}", SystemTime::now());
-        tokio::time::sleep(Duration::from_secs(30)).await;
+    for id in stale_ids.iter() {
+        if let Some(event) = pending_events.remove(id) {
+            println!("Pending event removed {}", &event.summary);
+        }
+        fired_stages.remove(id);
+        changed = true;
     }
+    changed
+}
+
+/// Add today's upcoming and all-day events to the pending alert queue
+async fn queue_upcoming_events(handle: &AppHandle) {
+    let state = handle.state::<AppState>();
+    let preferences = state.preferences.lock().await.get_state();
+    let calendars = state.calendars.lock().await;
+    let now = chrono::Utc::now();
+    let mut pending_events = state.pending_events.lock().unwrap();
+    for event in calendars
+        .upcoming_events()
+        .into_iter()
+        .chain(calendars.all_day_events())
+    {
+        // all-day events have started, only queue them until their notification is due
+        let has_future_deadline = calendars
+            .stage_deadlines(&event, &preferences)
+            .iter()
+            .any(|(_, deadline)| *deadline > now);
+        let upcoming = event
+            .start
+            .clone()
+            .map_or(false, |start| parse_event_datetime(start) > now);
+        if upcoming || has_future_deadline || pending_events.contains_key(&event.id) {
+            pending_events.insert(event.id.clone(), event);
+        }
+    }
+
+    // drop events filtered out by the meeting, invitation and all-day preferences
+    pending_events.retain(|_, event| !calendars.alert_stages(event, &preferences).is_empty());
+}
+
+/// Earliest moment a pending alert stage or snoozed alert is due
+async fn next_alert_deadline(handle: &AppHandle) -> Option<DateTime<Utc>> {
+    let state = handle.state::<AppState>();
+    let preferences = state.preferences.lock().await.get_state();
+    let calendars = state.calendars.lock().await;
+    let pending_events = state.pending_events.lock().unwrap();
+    let fired_stages = state.fired_stages.lock().unwrap();
+
+    let stage_deadlines = pending_events.values().flat_map(|event| {
+        let fired = fired_stages.get(&event.id);
+        calendars
+            .stage_deadlines(event, &preferences)
+            .into_iter()
+            .filter(move |(stage, _)| fired.map_or(true, |stages| !stages.contains(stage)))
+            .map(|(_, deadline)| deadline)
+    });
+
+    let snoozed_alerts = state.snoozed_alerts.lock().unwrap();
+    let snooze_deadlines = snoozed_alerts.values().map(|snoozed| snoozed.until);
+
+    let active_events = calendars.active_events();
+    let wrapped_up_events = state.wrapped_up_events.lock().unwrap();
+    let wrap_up_minutes = preferences.wrap_up_minutes();
+    let wrap_up_deadlines = active_events
+        .iter()
+        .filter(|event| !wrapped_up_events.contains(&event.id))
+        .filter(|event| !calendars.alert_stages(event, &preferences).is_empty())
+        .filter_map(|event| {
+            let minutes = wrap_up_minutes?;
+            let end = parse_event_datetime(event.end.clone()?);
+            Some(end - chrono::Duration::minutes(minutes))
+        });
+
+    stage_deadlines
+        .chain(snooze_deadlines)
+        .chain(wrap_up_deadlines)
+        .min()
+}
+
+/// Notify about ongoing events ending within the wrap-up window, mentioning a
+/// back-to-back meeting when one follows
+async fn dispatch_wrap_ups(handle: &AppHandle) {
+    let state = handle.state::<AppState>();
+    let preferences = state.preferences.lock().await.get_state();
+    let minutes = match preferences.wrap_up_minutes() {
+        Some(minutes) => minutes,
+        None => return,
+    };
+
+    let (active_events, upcoming_events) = {
+        let calendars = state.calendars.lock().await;
+        let active_events = calendars
+            .active_events()
+            .into_iter()
+            .filter(|event| !calendars.alert_stages(event, &preferences).is_empty())
+            .collect::<Vec<Event>>();
+        (active_events, calendars.upcoming_events())
+    };
+    let now = chrono::Utc::now();
+    let language = preferences.language();
+
+    let mut wrapped_up_events = state.wrapped_up_events.lock().unwrap();
+    wrapped_up_events.retain(|id| active_events.iter().any(|event| &event.id == id));
+
+    for event in active_events.iter() {
+        if wrapped_up_events.contains(&event.id) || event.end.is_none() {
+            continue;
+        }
+        let end = parse_event_datetime(event.end.clone().unwrap());
+        if now < end - chrono::Duration::minutes(minutes) {
+            continue;
+        }
+
+        let ends = time_to_relative_format(event.end.clone().unwrap(), language);
+        let body = match next_back_to_back(event, &upcoming_events) {
+            Some(next) => fill(
+                language.text(Phrase::EndsThenNext),
+                &[
+                    &event.summary,
+                    &ends,
+                    &next.summary,
+                    &get_human_readable_time(
+                        get_date_time(next, preferences.timezone()),
+                        preferences.time_format(),
+                    ),
+                ],
+            ),
+            None => fill(language.text(Phrase::Ends), &[&event.summary, &ends]),
+        };
+        println!("Wrap up: {}", &body);
+
+        Notification::new(&handle.config().tauri.bundle.identifier)
+            .title(fill(language.text(Phrase::WrapUp), &[&event.summary]))
+            .body(body)
+            .sound(Sound::Default)
+            .show()
+            .unwrap();
+        wrapped_up_events.insert(event.id.clone());
+    }
+}
+
+/// How long after an event started a stage due at its start still counts as on time
+const START_GRACE_SECONDS: i64 = 60;
+
+/// Whether a stage fires after its timed event started, e.g. when the machine slept
+/// through the deadline
+fn is_late(event: &Event, now: DateTime<Utc>) -> bool {
+    !is_all_day(event)
+        && event.start.clone().map_or(false, |start| {
+            now > parse_event_datetime(start) + chrono::Duration::seconds(START_GRACE_SECONDS)
+        })
+}
+
+/// Fire every alert stage and snoozed alert that is due
+async fn dispatch_due_alerts(handle: &AppHandle) {
+    let state = handle.state::<AppState>();
+    let preferences = state.preferences.lock().await.get_state();
+    let now = chrono::Utc::now();
+    let mut due_stages: Vec<(AlertStage, Event)> = vec![];
+    {
+        let calendars = state.calendars.lock().await;
+        let pending_events = state.pending_events.lock().unwrap();
+        let fired_stages = state.fired_stages.lock().unwrap();
+        for (_, event) in pending_events.iter() {
+            for (stage, deadline) in calendars.stage_deadlines(event, &preferences) {
+                if deadline > now {
+                    continue;
+                }
+                let fired = fired_stages
+                    .get(&event.id)
+                    .map_or(false, |stages| stages.contains(&stage));
+                if !fired {
+                    println!("{:?} stage due for {}", &stage, &event.summary);
+                    due_stages.push((stage, event.clone()));
+                }
+            }
+        }
+    }
+
+    // bring back snoozed alerts whose delay has elapsed
+    let snoozed_events = {
+        let mut snoozed_alerts = state.snoozed_alerts.lock().unwrap();
+        let due = snoozed_alerts
+            .values()
+            .filter(|snoozed| snoozed.until <= now)
+            .map(|snoozed| snoozed.event.clone())
+            .collect::<Vec<Event>>();
+        snoozed_alerts.retain(|_, snoozed| snoozed.until > now);
+        due
+    };
+    for event in snoozed_events.iter() {
+        println!("Snooze elapsed for {}", &event.summary);
+        show_event_alert(handle, event, AlertOutcome::Fired).await;
+    }
+
+    for (stage, event) in due_stages {
+        match stage {
+            AlertStage::Notification => {
+                if is_late(&event, now) {
+                    // the notification was due while the app was not running or asleep
+                    println!("Skipping late notification for {}", &event.summary);
+                } else {
+                    let language = preferences.language();
+                    let minutes = event.start.clone().map_or(0, |start| {
+                        ((parse_event_datetime(start) - now).num_seconds() + 59) / 60
+                    });
+                    let body = if is_all_day(&event) {
+                        fill(language.text(Phrase::Today), &[&event.summary])
+                    } else if minutes > 0 {
+                        fill(
+                            language.text(Phrase::StartsIn),
+                            &[&event.summary, &minutes.to_string()],
+                        )
+                    } else {
+                        fill(language.text(Phrase::StartsNow), &[&event.summary])
+                    };
+
+                    Notification::new(&handle.config().tauri.bundle.identifier)
+                        .title(event.summary.to_string())
+                        .body(body)
+                        .sound(Sound::Default)
+                        .show()
+                        .unwrap();
+                }
+            }
+            AlertStage::Alert => {
+                let ended = event
+                    .end
+                    .clone()
+                    .map_or(false, |end| parse_event_datetime(end) <= now);
+                if ended {
+                    // the alert was due while the app was not running or asleep
+                    state
+                        .alert_history
+                        .lock()
+                        .unwrap()
+                        .record(&event, AlertOutcome::Missed);
+                    state
+                        .fired_stages
+                        .lock()
+                        .unwrap()
+                        .entry(event.id.clone())
+                        .or_default()
+                        .insert(stage);
+                    continue;
+                }
+
+                println!(
+                    "================Alert Event : {} {:?}=========",
+                    &event.summary,
+                    &event.start.clone().unwrap()
+                );
+                // shown after the event started, its alert was missed
+                let outcome = if is_late(&event, now) {
+                    AlertOutcome::Missed
+                } else {
+                    AlertOutcome::Fired
+                };
+                show_event_alert(handle, &event, outcome).await;
+            }
+        }
+
+        state
+            .fired_stages
+            .lock()
+            .unwrap()
+            .entry(event.id.clone())
+            .or_default()
+            .insert(stage);
+    }
+
+    // drop started events once every stage has fired
+    let calendars = state.calendars.lock().await;
+    let mut pending_events = state.pending_events.lock().unwrap();
+    let mut fired_stages = state.fired_stages.lock().unwrap();
+    pending_events.retain(|id, event| {
+        let started = event
+            .start
+            .clone()
+            .map_or(true, |start| parse_event_datetime(start) <= now);
+        let all_fired = fired_stages.get(id).map_or(false, |stages| {
+            calendars
+                .alert_stages(event, &preferences)
+                .iter()
+                .all(|stage| stages.contains(stage))
+        });
+        !(started && all_fired)
+    });
+    fired_stages.retain(|id, _| pending_events.contains_key(id));
 }
 
 /// Migrate app state from google_auth.json to accounts.json file
@@ -287,8 +812,23 @@ pub async fn start(app: AppHandle) -> std::io::Result<()> {
     let preferences = preferences.map_or(Preferences::default(), |pref| pref);
 
     println!("Preferences: {:?}", &preferences);
+    let timezone = preferences.timezone();
     *app.state::<AppState>().preferences.lock().await = preferences;
 
+    let data_path = tauri::api::path::app_data_dir(&app.config()).unwrap_or(PathBuf::default());
+    *app.state::<AppState>().alert_history.lock().unwrap() =
+        AlertHistory::load_from_file(data_path.clone());
+
+    // show the last synced events right away, accounts take a while to connect
+    let cache = EventCache::load_from_file(data_path);
+    {
+        let state = app.state::<AppState>();
+        let calendars = state.calendars.lock().await;
+        calendars.set_timezone(timezone);
+        calendars.load_cache(cache.clone());
+    }
+    let _ = update_try_app(&app).await;
+
     let tokens = read_account_state(&app).await;
     if tokens.is_ok() {
         let tokens = tokens
@@ -302,7 +842,7 @@ pub async fn start(app: AppHandle) -> std::io::Result<()> {
             let config = app.state::<AppState>().app_config.lock().unwrap().clone();
             let state = app.state::<AppState>();
             let preferences = state.preferences.lock().await;
-            let calendar = Calendars::new(tokens, config, &preferences).await;
+            let calendar = Calendars::new(tokens, config, &preferences, cache).await;
             *app.state::<AppState>().calendars.lock().await = calendar;
         }
     } else {
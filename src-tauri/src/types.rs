@@ -1,5 +1,10 @@
 use crate::account::Calendars;
+use crate::history::AlertHistory;
+use crate::locale::{fill, Phrase};
+use chrono::{DateTime, Utc};
+use chrono_tz::Tz;
 use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
 use std::fs;
 use std::io::Write;
 use std::sync::Mutex;
@@ -10,6 +15,11 @@ pub struct TauriAppState {
     pub app: AppHandle,
 }
 
+/// Agenda horizon in days, today and tomorrow are always shown
+pub const MIN_AGENDA_DAYS: i64 = 2;
+pub const DEFAULT_AGENDA_DAYS: i64 = 3;
+pub const MAX_AGENDA_DAYS: i64 = 7;
+
 // todo: add Account Settings to support per account preferences (calenders to exclude, etc)
 // todo: add field for app settings/preferences
 
@@ -18,6 +28,20 @@ pub struct AppState {
     pub google_auth_credentials: Mutex<GoogleAuthToken>,
     pub calendars: tokio::sync::Mutex<Calendars>,
     pub pending_events: Mutex<HashMap<String, google_calendar::types::Event>>,
+    /// Alert stages already dispatched, keyed by event id
+    pub fired_stages: Mutex<HashMap<String, HashSet<AlertStage>>>,
+    /// Events currently shown in the alert window
+    pub alert_queue: Mutex<Vec<google_calendar::types::Event>>,
+    /// Alerts put off by the user, keyed by event id
+    pub snoozed_alerts: Mutex<HashMap<String, SnoozedAlert>>,
+    pub alert_history: Mutex<AlertHistory>,
+    /// Notified whenever pending alerts change so the scheduler recomputes its deadline
+    pub schedule_changed: tokio::sync::Notify,
+    /// Ongoing events that already got a wrap-up notification
+    pub wrapped_up_events: Mutex<HashSet<String>>,
+    pub rendered_tray: Mutex<RenderedTray>,
+    /// Account sync status last sent to the UI, to only send it when it changed
+    pub sync_status: Mutex<Vec<AccountSyncStatus>>,
     pub alert_size: Mutex<PhysicalSize<u32>>,
     pub alert_position: Mutex<PhysicalPosition<i32>>,
     pub app_config: Mutex<AppCredentials>,
@@ -25,6 +49,15 @@ pub struct AppState {
     pub preferences: tokio::sync::Mutex<Preferences>,
 }
 
+/// Tray title and menu items (id, title) as last rendered, diffed on every update
+#[derive(Debug, Default)]
+pub struct RenderedTray {
+    pub title: String,
+    pub items: Vec<(String, String)>,
+    /// Language of the menu footer
+    pub language: Language,
+}
+
 #[derive(Serialize, Deserialize, Clone, Debug, Default)]
 pub struct AppCredentials {
     // Todo: redact private data with secrecy package
@@ -73,11 +106,313 @@ pub struct Preferences {
     storage_path: String,
     notify_only_meetings: bool,
     accounts_preferences: HashMap<String, AccountPreference>,
+    /// Minutes before an ongoing event ends to send a wrap-up notification
+    wrap_up_minutes: Option<i64>,
+    /// Alerting for `tentative` and `needsAction` invitations
+    #[serde(default)]
+    pending_invitations: InvitationPolicy,
+    #[serde(default)]
+    all_day_events: AllDayPolicy,
+    /// Local hour of the all-day events morning notification
+    morning_notification_hour: Option<u32>,
+    #[serde(default)]
+    tray_title: TrayTitleFormat,
+    /// Longest event title shown in the tray before it is truncated
+    tray_title_max_length: Option<usize>,
+    #[serde(default)]
+    event_click_action: EventClickAction,
+    /// Days ahead, today included, that events are fetched and grouped for
+    agenda_days: Option<i64>,
+    #[serde(default)]
+    time_format: TimeFormat,
+    #[serde(default)]
+    language: Language,
+    /// IANA timezone events are shown in instead of the system one
+    timezone: Option<String>,
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize, Default)]
 pub struct AccountPreference {
     pub hidden_calendars: Vec<String>,
+    #[serde(default)]
+    pub lead_times: LeadTimes,
+    /// Per calendar overrides of the account lead times, keyed by calendar id
+    #[serde(default)]
+    pub calendar_lead_times: HashMap<String, LeadTimes>,
+    /// Derive lead times from the popup reminders configured in Google
+    #[serde(default)]
+    pub use_google_reminders: bool,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct SnoozedAlert {
+    pub event: google_calendar::types::Event,
+    pub until: DateTime<Utc>,
+}
+
+/// Account and calendar an event was fetched from
+#[derive(Clone, Debug, Serialize, Deserialize, Default, PartialEq)]
+pub struct EventSource {
+    pub account: String,
+    pub calendar_id: String,
+    /// The account's response to the invitation (`accepted`, `tentative`, `needsAction`...)
+    #[serde(default)]
+    pub response_status: String,
+}
+
+/// How to alert for invitations the user hasn't accepted yet
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum InvitationPolicy {
+    Alert,
+    /// Send the notification but skip the fullscreen alert
+    NotifyOnly,
+    Ignore,
+}
+
+impl Default for InvitationPolicy {
+    fn default() -> Self {
+        InvitationPolicy::Alert
+    }
+}
+
+/// How to alert for all-day events
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AllDayPolicy {
+    Off,
+    /// Send a notification on the morning of the event
+    MorningNotification,
+}
+
+impl Default for AllDayPolicy {
+    fn default() -> Self {
+        AllDayPolicy::Off
+    }
+}
+
+/// What the tray title shows next to the icon
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TrayTitleFormat {
+    /// "Standup in 4m" or "Ends in 12m"
+    Countdown,
+    /// Title of the ongoing or next event
+    NextEventTitle,
+    IconOnly,
+}
+
+impl Default for TrayTitleFormat {
+    fn default() -> Self {
+        TrayTitleFormat::Countdown
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TimeFormat {
+    /// "3:05 PM"
+    TwelveHour,
+    /// "15:05"
+    TwentyFourHour,
+}
+
+impl Default for TimeFormat {
+    fn default() -> Self {
+        TimeFormat::TwelveHour
+    }
+}
+
+/// Language of the tray labels, notifications and relative times
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Language {
+    En,
+    Fr,
+    De,
+    Es,
+}
+
+impl Default for Language {
+    fn default() -> Self {
+        Language::En
+    }
+}
+
+/// What clicking an event in the tray does
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EventClickAction {
+    /// Show the event in the main window
+    ShowDetails,
+    /// Open the event's `html_link` in Google Calendar
+    OpenInCalendar,
+}
+
+impl Default for EventClickAction {
+    fn default() -> Self {
+        EventClickAction::ShowDetails
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ConflictKind {
+    /// Events running at the same time
+    Overlap,
+    /// Event starting right as the previous one ends
+    BackToBack,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct EventConflict {
+    pub kind: ConflictKind,
+    pub event_id: String,
+    pub summary: String,
+    pub accounts: Vec<String>,
+    pub other_event_id: String,
+    pub other_summary: String,
+    pub other_accounts: Vec<String>,
+}
+
+impl EventConflict {
+    /// Warning shown for `event_id`, naming the event it conflicts with
+    pub fn warning_for(&self, event_id: &str, language: Language) -> Option<String> {
+        let (summary, accounts) = if self.event_id == event_id {
+            (&self.other_summary, &self.other_accounts)
+        } else if self.other_event_id == event_id {
+            (&self.summary, &self.accounts)
+        } else {
+            return None;
+        };
+
+        let (with, with_from) = match self.kind {
+            ConflictKind::Overlap => (Phrase::OverlapsWith, Phrase::OverlapsWithFrom),
+            ConflictKind::BackToBack => (Phrase::BackToBackWith, Phrase::BackToBackWithFrom),
+        };
+        match accounts.first() {
+            Some(account) => Some(fill(
+                language.text(with_from),
+                &[summary.as_str(), account.as_str()],
+            )),
+            None => Some(fill(language.text(with), &[summary.as_str()])),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SyncState {
+    /// Not synced since the app started and nothing cached
+    Pending,
+    Syncing,
+    Synced,
+    /// Last sync failed, the account is retried at `retry_at`
+    Failing,
+    Disabled,
+}
+
+/// Sync diagnostics of an account shown in the UI
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct AccountSyncStatus {
+    pub account: String,
+    pub state: SyncState,
+    pub last_synced_at: Option<DateTime<Utc>>,
+    pub last_error: Option<String>,
+    pub retry_at: Option<DateTime<Utc>>,
+    pub token_expires_at: Option<DateTime<Utc>>,
+    pub calendar_count: usize,
+}
+
+/// Event sent to the alert window with warnings about conflicting events
+#[derive(Clone, Debug, Serialize)]
+pub struct AlertPayload {
+    #[serde(flatten)]
+    pub event: google_calendar::types::Event,
+    pub warnings: Vec<String>,
+    pub join_url: Option<String>,
+    /// Start and end times in the user's time format, e.g. "15:00 - 15:30"
+    pub time_label: String,
+    /// Start time in the event's own timezone when it differs from the displayed one
+    pub original_time_label: Option<String>,
+    pub labels: AlertLabels,
+}
+
+/// Alert page strings in the user's language, the page fills `{}` with the snooze
+/// minutes or a relative time
+#[derive(Clone, Debug, Serialize)]
+pub struct AlertLabels {
+    pub language: Language,
+    pub ok: String,
+    pub join_meeting: String,
+    pub join_google_meet: String,
+    pub join_zoom: String,
+    pub snooze: String,
+    pub started: String,
+    pub starts: String,
+    pub ended: String,
+}
+
+impl AlertLabels {
+    pub fn new(language: Language) -> Self {
+        AlertLabels {
+            language,
+            ok: language.text(Phrase::Ok).to_string(),
+            join_meeting: language.text(Phrase::JoinMeeting).to_string(),
+            join_google_meet: language.text(Phrase::JoinGoogleMeet).to_string(),
+            join_zoom: language.text(Phrase::JoinZoom).to_string(),
+            snooze: language.text(Phrase::SnoozeMinutes).to_string(),
+            started: language.text(Phrase::EventStarted).to_string(),
+            starts: language.text(Phrase::EventStarts).to_string(),
+            ended: language.text(Phrase::EventEnded).to_string(),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AlertStage {
+    /// System notification
+    Notification,
+    /// Fullscreen alert window
+    Alert,
+}
+
+/// Minutes before an event starts at which each alert stage fires
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, Default, PartialEq)]
+pub struct LeadTimes {
+    #[serde(default)]
+    pub notification: i64,
+    #[serde(default)]
+    pub alert: i64,
+}
+
+impl LeadTimes {
+    pub fn minutes(&self, stage: AlertStage) -> i64 {
+        match stage {
+            AlertStage::Notification => self.notification,
+            AlertStage::Alert => self.alert,
+        }
+    }
+
+    /// Lead times from Google popup reminder minutes, the earliest reminder drives the
+    /// notification and the latest one the fullscreen alert
+    pub fn from_reminders(minutes: &[i64]) -> Option<LeadTimes> {
+        let notification = *minutes.iter().max()?;
+        let alert = *minutes.iter().min()?;
+        Some(LeadTimes {
+            notification,
+            alert,
+        })
+    }
+
+    /// Lead times firing each stage at the earlier of the two
+    pub fn earliest(self, other: LeadTimes) -> LeadTimes {
+        LeadTimes {
+            notification: self.notification.max(other.notification),
+            alert: self.alert.max(other.alert),
+        }
+    }
 }
 
 impl Preferences {
@@ -150,6 +485,56 @@ impl Preferences {
         //     })
     }
 
+    /// Lead times for a calendar, falling back to the account lead times
+    pub fn get_lead_times(&self, account: &str, calendar_id: &str) -> LeadTimes {
+        if let Some(account_pref) = self.accounts_preferences.get(account) {
+            account_pref
+                .calendar_lead_times
+                .get(calendar_id)
+                .map_or(account_pref.lead_times, |lead_times| *lead_times)
+        } else {
+            LeadTimes::default()
+        }
+    }
+
+    pub async fn set_lead_times(
+        &mut self,
+        account: String,
+        calendar_id: Option<String>,
+        lead_times: LeadTimes,
+    ) -> Result<(), String> {
+        let account_pref = self
+            .accounts_preferences
+            .entry(account)
+            .or_insert_with(AccountPreference::default);
+
+        match calendar_id {
+            Some(calendar_id) => {
+                account_pref
+                    .calendar_lead_times
+                    .insert(calendar_id, lead_times);
+            }
+            None => account_pref.lead_times = lead_times,
+        }
+
+        let _ = self.save_state().await;
+        Ok(())
+    }
+
+    pub async fn set_use_google_reminders(
+        &mut self,
+        account: String,
+        value: bool,
+    ) -> Result<(), String> {
+        self.accounts_preferences
+            .entry(account)
+            .or_insert_with(AccountPreference::default)
+            .use_google_reminders = value;
+
+        let _ = self.save_state().await;
+        Ok(())
+    }
+
     pub fn is_calendar_hidden(&self, account: &str, calendar_id: &String) -> bool {
         if let Some(account_pref) = self.accounts_preferences.get(account) {
             account_pref.hidden_calendars.contains(calendar_id)
@@ -157,11 +542,124 @@ impl Preferences {
             false
         }
     }
+    pub fn notify_only_meetings(&self) -> bool {
+        self.notify_only_meetings
+    }
+
     pub async fn set_notify_only_meetings(&mut self, value: bool) {
         self.notify_only_meetings = value;
         let _ = self.save_state().await;
     }
 
+    pub fn wrap_up_minutes(&self) -> Option<i64> {
+        self.wrap_up_minutes
+    }
+
+    pub async fn set_wrap_up_minutes(&mut self, minutes: Option<i64>) {
+        self.wrap_up_minutes = minutes;
+        let _ = self.save_state().await;
+    }
+
+    pub fn pending_invitations(&self) -> InvitationPolicy {
+        self.pending_invitations
+    }
+
+    pub async fn set_pending_invitations(&mut self, policy: InvitationPolicy) {
+        self.pending_invitations = policy;
+        let _ = self.save_state().await;
+    }
+
+    pub fn all_day_events(&self) -> AllDayPolicy {
+        self.all_day_events
+    }
+
+    pub fn morning_notification_hour(&self) -> u32 {
+        self.morning_notification_hour
+            .filter(|hour| *hour < 24)
+            .unwrap_or(9)
+    }
+
+    pub async fn set_all_day_events(
+        &mut self,
+        policy: AllDayPolicy,
+        hour: Option<u32>,
+    ) -> Result<(), String> {
+        if let Some(hour) = hour.filter(|hour| *hour > 23) {
+            return Err(format!("Invalid morning notification hour {}", hour));
+        }
+        self.all_day_events = policy;
+        if hour.is_some() {
+            self.morning_notification_hour = hour;
+        }
+        self.save_state().await
+    }
+
+    pub fn tray_title(&self) -> TrayTitleFormat {
+        self.tray_title
+    }
+
+    pub fn tray_title_max_length(&self) -> usize {
+        self.tray_title_max_length.unwrap_or(24)
+    }
+
+    pub async fn set_tray_title(&mut self, format: TrayTitleFormat, max_length: Option<usize>) {
+        self.tray_title = format;
+        if max_length.is_some() {
+            self.tray_title_max_length = max_length;
+        }
+        let _ = self.save_state().await;
+    }
+
+    pub fn agenda_days(&self) -> i64 {
+        self.agenda_days
+            .unwrap_or(DEFAULT_AGENDA_DAYS)
+            .clamp(MIN_AGENDA_DAYS, MAX_AGENDA_DAYS)
+    }
+
+    pub async fn set_agenda_days(&mut self, days: i64) {
+        self.agenda_days = Some(days.clamp(MIN_AGENDA_DAYS, MAX_AGENDA_DAYS));
+        let _ = self.save_state().await;
+    }
+
+    pub fn time_format(&self) -> TimeFormat {
+        self.time_format
+    }
+
+    pub fn language(&self) -> Language {
+        self.language
+    }
+
+    pub async fn set_locale(&mut self, time_format: TimeFormat, language: Language) {
+        self.time_format = time_format;
+        self.language = language;
+        let _ = self.save_state().await;
+    }
+
+    pub fn timezone(&self) -> Option<Tz> {
+        self.timezone
+            .as_ref()
+            .and_then(|timezone| timezone.parse().ok())
+    }
+
+    pub async fn set_timezone(&mut self, timezone: Option<String>) -> Result<(), String> {
+        if let Some(timezone) = &timezone {
+            timezone
+                .parse::<Tz>()
+                .map_err(|err| format!("Invalid timezone {}: {}", timezone, err))?;
+        }
+        self.timezone = timezone;
+        self.save_state().await
+    }
+
+    pub fn event_click_action(&self) -> EventClickAction {
+        self.event_click_action
+    }
+
+    pub async fn set_event_click_action(&mut self, action: EventClickAction) {
+        self.event_click_action = action;
+        let _ = self.save_state().await;
+    }
+
     pub async fn hide_calendar(
         &mut self,
         account: String,
@@ -217,6 +715,17 @@ impl Preferences {
             storage_path: String::from("[redacted]"),
             notify_only_meetings: self.notify_only_meetings.clone(),
             accounts_preferences: self.accounts_preferences.clone(),
+            wrap_up_minutes: self.wrap_up_minutes,
+            pending_invitations: self.pending_invitations,
+            all_day_events: self.all_day_events,
+            morning_notification_hour: self.morning_notification_hour,
+            tray_title: self.tray_title,
+            tray_title_max_length: self.tray_title_max_length,
+            event_click_action: self.event_click_action,
+            agenda_days: self.agenda_days,
+            time_format: self.time_format,
+            language: self.language,
+            timezone: self.timezone.clone(),
         }
     }
 }
@@ -1,13 +1,58 @@
-use chrono::{DateTime, TimeZone, Timelike, NaiveTime, Utc};
+use chrono::{DateTime, NaiveDate, NaiveTime, Offset, TimeZone, Timelike, Utc};
 use chrono_humanize;
 use chrono_tz::Tz;
-use serde::Deserialize;
+use crate::locale::{fill, relative_time, weekday, Phrase};
+use crate::types::{ConflictKind, Language, Preferences, TimeFormat, TrayTitleFormat};
+use google_calendar::types::Event;
+use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Clone, Default, Deserialize)]
+/// Largest gap between two events for them to count as back-to-back
+pub const BACK_TO_BACK_GAP_MINUTES: i64 = 5;
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct EventGroups {
+    /// All-day and multi-day events covering today
+    #[serde(default)]
+    pub all_day: Vec<google_calendar::types::Event>,
     pub now: Vec<google_calendar::types::Event>,
     pub upcoming: Vec<google_calendar::types::Event>,
     pub tomorrow: Vec<google_calendar::types::Event>,
+    /// Events after tomorrow, up to the agenda horizon
+    #[serde(default)]
+    pub later: Vec<google_calendar::types::Event>,
+    /// Events are from the cache or an earlier poll because the last poll failed
+    #[serde(default)]
+    pub stale: bool,
+    #[serde(default)]
+    pub synced_at: Option<DateTime<Utc>>,
+}
+
+/// Whether an event only has dates (holidays, OOO, birthdays...) rather than times
+pub fn is_all_day(event: &Event) -> bool {
+    event
+        .start
+        .as_ref()
+        .map_or(false, |start| start.date_time.is_none() && start.date.is_some())
+}
+
+/// Whether an all-day event covers `day`, Google end dates are exclusive
+pub fn all_day_covers(event: &Event, day: NaiveDate) -> bool {
+    let start = event.start.as_ref().and_then(|start| start.date);
+    let end = event.end.as_ref().and_then(|end| end.date);
+    match (start, end) {
+        (Some(start), Some(end)) => start <= day && day < end,
+        (Some(start), None) => start == day,
+        _ => false,
+    }
+}
+
+/// Day number and length in days of an all-day event on `day`, e.g. `(2, 3)` on the
+/// second day of a three day event
+pub fn all_day_span(event: &Event, day: NaiveDate) -> Option<(i64, i64)> {
+    let start = event.start.as_ref()?.date?;
+    let end = event.end.as_ref()?.date?;
+    let days = (end - start).num_days().max(1);
+    Some(((day - start).num_days() + 1, days))
 }
 
 pub fn parse_event_datetime(event_datetime: google_calendar::types::EventDateTime) -> DateTime<Utc> {
@@ -20,32 +65,273 @@ pub fn parse_event_datetime(event_datetime: google_calendar::types::EventDateTim
             "%Y-%m-%d",
         ).unwrap();
         let date_with_time =  date.and_time(NaiveTime::default());
-        let r = chrono::Local.from_local_datetime(&date_with_time).unwrap();
-        r.to_utc()
+        let r = local_timezone(None)
+            .from_local_datetime(&date_with_time)
+            .earliest()
+            .unwrap();
+        r.with_timezone(&Utc)
     }
 }
 
-pub fn with_local_timezone(date_time: DateTime<Utc>) -> DateTime<Tz> {
-    let tz_str = iana_time_zone::get_timezone().unwrap_or(chrono_tz::UTC.to_string());
-    let timezone: Tz = tz_str.parse().unwrap_or_else(|_| {
-        Tz::UTC
+/// Hosts of the video call services recognised in locations and descriptions
+const VIDEO_CALL_HOSTS: [&str; 7] = [
+    "meet.google.com",
+    "zoom.us",
+    "teams.microsoft.com",
+    "teams.live.com",
+    "webex.com",
+    "whereby.com",
+    "gotomeeting.com",
+];
+
+/// First video call url found in a free text field
+pub fn find_video_call_url(text: &str) -> Option<String> {
+    text.split(|c: char| c.is_whitespace() || c == '"' || c == '<' || c == '>')
+        .filter(|word| word.starts_with("https://") || word.starts_with("http://"))
+        .find(|url| VIDEO_CALL_HOSTS.iter().any(|host| url.contains(host)))
+        .map(|url| url.trim_end_matches(|c: char| c == ')' || c == ',' || c == '.').to_string())
+}
+
+/// Url to join the event's video call, from its Meet link, conference entry points
+/// or a link in its location or description
+pub fn join_url(event: &Event) -> Option<String> {
+    if !event.hangout_link.is_empty() {
+        return Some(event.hangout_link.clone());
+    }
+
+    let video_entry_point = event.conference_data.as_ref().and_then(|conference| {
+        conference
+            .entry_points
+            .iter()
+            .find(|entry_point| entry_point.entry_point_type == "video")
     });
-    date_time.with_timezone(&timezone)
+    if let Some(entry_point) = video_entry_point {
+        return Some(entry_point.uri.clone());
+    }
+
+    find_video_call_url(&event.location).or_else(|| find_video_call_url(&event.description))
+}
+
+/// Whether an event is a meeting rather than a plain calendar block, `accounts` are
+/// the emails of the accounts the event was fetched from
+pub fn is_meeting(event: &Event, accounts: &[String]) -> bool {
+    // rooms and the user's own entry are attendees too
+    let has_other_attendees = event
+        .attendees
+        .iter()
+        .any(|attendee| !attendee.resource && !accounts.contains(&attendee.email));
+    join_url(event).is_some() || event.conference_data.is_some() || has_other_attendees
+}
+
+/// First event in `upcoming` starting within the back-to-back gap after `event` ends
+pub fn next_back_to_back<'a>(event: &Event, upcoming: &'a [Event]) -> Option<&'a Event> {
+    let end = parse_event_datetime(event.end.clone()?);
+    upcoming.iter().find(|next| {
+        next.id != event.id
+            && next.start.clone().map_or(false, |start| {
+                let start = parse_event_datetime(start);
+                start >= end - chrono::Duration::minutes(BACK_TO_BACK_GAP_MINUTES)
+                    && start <= end + chrono::Duration::minutes(BACK_TO_BACK_GAP_MINUTES)
+            })
+    })
+}
+
+/// Pairs of timed events, by index, that overlap or run back-to-back
+pub fn find_conflicts(events: &[Event]) -> Vec<(ConflictKind, usize, usize)> {
+    let mut timed = events
+        .iter()
+        .enumerate()
+        .filter_map(|(idx, event)| {
+            let start = event.start.clone()?.date_time?;
+            let end = event.end.clone()?.date_time?;
+            Some((idx, start, end))
+        })
+        .collect::<Vec<(usize, DateTime<Utc>, DateTime<Utc>)>>();
+    timed.sort_by_key(|(_, start, _)| *start);
+
+    let gap = chrono::Duration::minutes(BACK_TO_BACK_GAP_MINUTES);
+    let mut conflicts = vec![];
+    for (position, (idx, _, end)) in timed.iter().enumerate() {
+        for (other_idx, other_start, _) in timed.iter().skip(position + 1) {
+            if other_start < end {
+                conflicts.push((ConflictKind::Overlap, *idx, *other_idx));
+            } else if *other_start <= *end + gap {
+                conflicts.push((ConflictKind::BackToBack, *idx, *other_idx));
+            } else {
+                break;
+            }
+        }
+    }
+    conflicts
+}
+
+/// Compact countdown such as "4m" or "1h 5m"
+pub fn format_countdown(duration: chrono::Duration, language: Language) -> String {
+    let minutes = (duration.num_seconds() + 59) / 60;
+    let minutes = minutes.max(0);
+    if minutes < 60 {
+        fill(language.text(Phrase::CountdownMinutes), &[&minutes.to_string()])
+    } else if minutes % 60 == 0 {
+        fill(language.text(Phrase::CountdownHours), &[&(minutes / 60).to_string()])
+    } else {
+        fill(
+            language.text(Phrase::CountdownHoursMinutes),
+            &[&(minutes / 60).to_string(), &(minutes % 60).to_string()],
+        )
+    }
 }
 
-pub fn get_date_time(event: &google_calendar::types::Event) -> DateTime<Tz> {
+/// Shorten `title` to `max_length` characters, ending with an ellipsis when cut
+pub fn truncate_title(title: &str, max_length: usize) -> String {
+    if title.chars().count() <= max_length {
+        return title.to_string();
+    }
+    let truncated = title
+        .chars()
+        .take(max_length.saturating_sub(1))
+        .collect::<String>();
+    format!("{}…", truncated.trim_end())
+}
+
+/// Tray title for the current event groups, empty when the day is clear
+pub fn tray_title(groups: &EventGroups, preferences: &Preferences, now: DateTime<Utc>) -> String {
+    let max_length = preferences.tray_title_max_length();
+    let language = preferences.language();
+    match preferences.tray_title() {
+        TrayTitleFormat::IconOnly => String::new(),
+        TrayTitleFormat::NextEventTitle => groups
+            .now
+            .first()
+            .or_else(|| groups.upcoming.first())
+            .map_or(String::new(), |event| {
+                truncate_title(&event.summary, max_length)
+            }),
+        TrayTitleFormat::Countdown => {
+            if let Some(event) = groups.now.first() {
+                let end = parse_event_datetime(event.end.clone().unwrap());
+                fill(
+                    language.text(Phrase::EndsInShort),
+                    &[&format_countdown(end - now, language)],
+                )
+            } else if let Some(event) = groups.upcoming.first() {
+                let start = parse_event_datetime(event.start.clone().unwrap());
+                fill(
+                    language.text(Phrase::StartsInShort),
+                    &[
+                        &truncate_title(&event.summary, max_length),
+                        &format_countdown(start - now, language),
+                    ],
+                )
+            } else {
+                String::new()
+            }
+        }
+    }
+}
+
+/// Timezone events are displayed and grouped in, the `timezone` override from the
+/// preferences or the system one
+pub fn local_timezone(timezone: Option<Tz>) -> Tz {
+    timezone.unwrap_or_else(|| {
+        let tz_str = iana_time_zone::get_timezone().unwrap_or(chrono_tz::UTC.to_string());
+        tz_str.parse().unwrap_or_else(|_| Tz::UTC)
+    })
+}
+
+pub fn local_now(timezone: Option<Tz>) -> DateTime<Tz> {
+    with_local_timezone(Utc::now(), timezone)
+}
+
+pub fn with_local_timezone(date_time: DateTime<Utc>, timezone: Option<Tz>) -> DateTime<Tz> {
+    date_time.with_timezone(&local_timezone(timezone))
+}
+
+/// Start time in the event's own timezone, e.g. "6:00 AM PDT", when it differs
+/// from the displayed timezone
+pub fn original_time_label(
+    event: &Event,
+    time_format: TimeFormat,
+    timezone: Option<Tz>,
+) -> Option<String> {
+    let start = event.start.as_ref()?;
+    if start.date_time.is_none() {
+        return None;
+    }
+    let event_timezone: Tz = start.time_zone.parse().ok()?;
+    let local = get_date_time(event, timezone);
+    let original = local.with_timezone(&event_timezone);
+    if original.offset().fix() == local.offset().fix() {
+        return None;
+    }
+    Some(format!(
+        "{} {}",
+        get_human_readable_time(original, time_format),
+        original.format("%Z")
+    ))
+}
+
+pub fn get_date_time(
+    event: &google_calendar::types::Event,
+    timezone: Option<Tz>,
+) -> DateTime<Tz> {
     let datetime = parse_event_datetime(event.start.clone().unwrap());
-    with_local_timezone(datetime)
+    with_local_timezone(datetime, timezone)
 }
 
-pub fn time_to_relative_format(event_datetime: google_calendar::types::EventDateTime) -> String {
+/// Short weekday ("Thu") for events starting after tomorrow
+pub fn later_day_label(
+    event: &Event,
+    language: Language,
+    timezone: Option<Tz>,
+) -> Option<String> {
+    let start = if is_all_day(event) {
+        event.start.as_ref()?.date?
+    } else {
+        get_date_time(event, timezone).date_naive()
+    };
+    let today = local_now(timezone).date_naive();
+    if (start - today).num_days() > 1 {
+        Some(weekday(language, start))
+    } else {
+        None
+    }
+}
+
+/// "3:00 PM - 3:30 PM", or the all-day label for date only events
+pub fn event_time_label(
+    event: &Event,
+    time_format: TimeFormat,
+    language: Language,
+    timezone: Option<Tz>,
+) -> String {
+    if is_all_day(event) || event.end.is_none() {
+        return language.text(Phrase::AllDay).to_string();
+    }
+    let end = with_local_timezone(parse_event_datetime(event.end.clone().unwrap()), timezone);
+    format!(
+        "{} - {}",
+        get_human_readable_time(get_date_time(event, timezone), time_format),
+        get_human_readable_time(end, time_format)
+    )
+}
+
+pub fn time_to_relative_format(
+    event_datetime: google_calendar::types::EventDateTime,
+    language: Language,
+) -> String {
     let datetime = parse_event_datetime(event_datetime);
-    let dt = with_local_timezone(datetime);
+    if language != Language::En {
+        return relative_time(language, datetime - Utc::now());
+    }
+    let dt = with_local_timezone(datetime, None);
     chrono_humanize::HumanTime::from(dt).to_string()
 }
 
-pub fn get_human_readable_time(time: DateTime<Tz>) -> String {
+pub fn get_human_readable_time(time: DateTime<Tz>, time_format: TimeFormat) -> String {
     let hour24 = time.hour();
+    if time_format == TimeFormat::TwentyFourHour {
+        return format!("{:02}:{:02}", hour24, time.minute());
+    }
 
     let (_, hour) = time.hour12();
     let is_pm = hour24 >= 12;
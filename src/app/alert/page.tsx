@@ -1,10 +1,11 @@
 "use client";
 import {Providers} from "@/AppProviders";
 import {Button} from "@/components/ui/button";
-import {useAlert, useUser} from "@/slices/hooks";
+import {useUser} from "@/slices/hooks";
 import {invoke} from "@tauri-apps/api/tauri";
 import {useCallback, useEffect, useMemo, useState} from "react";
-import {formatDistance} from "date-fns";
+import {formatDistance, Locale} from "date-fns";
+import {de, enUS, es, fr} from "date-fns/locale";
 import {cn} from "@/lib/utils";
 import {Avatar, AvatarFallback, AvatarImage} from "@/components/ui/avatar";
 import {
@@ -15,20 +16,85 @@ import {open} from "@tauri-apps/api/shell";
 import {GoogleMeetIcon, ZoomMeetIcon} from "@/components/icons/icons";
 import {listen} from "@tauri-apps/api/event";
 import {GoogleAuthToken, setToken} from "@/slices/authSlice";
-import {setAlert} from "@/slices/alert";
+import {AlertEvent, AlertLabels, setAlert} from "@/slices/alert";
 import {useSetter} from "@/store/accessors";
 
-function AlertInfo() {
+const SNOOZE_OPTIONS = [1, 5, 10];
+
+const LOCALES: Record<AlertLabels["language"], Locale> = {en: enUS, fr, de, es};
+
+const DEFAULT_LABELS: AlertLabels = {
+    language: "en",
+    ok: "Ok",
+    join_meeting: "Join meeting",
+    join_google_meet: "Join Google meet meeting",
+    join_zoom: "Join Zoom meeting",
+    snooze: "Snooze {} min",
+    started: "This event started {}",
+    starts: "This event will start {}",
+    ended: "This event ended {}",
+};
+
+const fill = (template: string, value: string | number) =>
+    template.replace("{}", String(value));
+
+function AlertQueue() {
     const dispatch = useSetter();
-    const alert = useAlert();
+    const [alerts, setAlerts] = useState<AlertEvent[]>([]);
+
+    useEffect(() => {
+        invoke<AlertEvent[]>("get_alert_queue").then(setAlerts);
+
+        let unlisten = () => {
+        };
+        const registerListener = async () => {
+            unlisten = await listen<AlertEvent[]>("alerts", (event) => {
+                console.log("ALERTS", event.payload);
+                setAlerts(event.payload);
+                if (event.payload.length > 0) dispatch(setAlert(event.payload[0]));
+            });
+        };
+        registerListener();
+
+        return () => {
+            unlisten();
+        };
+    }, [dispatch]);
+
+    if (alerts.length === 0) return;
+
+    return (
+        <main
+            className="bg-background flex h-full min-h-screen flex-col items-center justify-center rounded-md p-24 backdrop-blur-md gap-12 overflow-y-auto">
+            {alerts.map((alert) => (
+                <AlertInfo key={alert.id} alert={alert}/>
+            ))}
+        </main>
+    );
+}
+
+function AlertInfo({alert}: { alert: AlertEvent }) {
     const [timeLabel, setTimeLabel] = useState<string>();
     const [runningLate, setRunningLate] = useState(false);
+    const labels = alert.labels ?? DEFAULT_LABELS;
     console.log('ALERT', alert);
     const closeAlert = async () => {
-        let res = await invoke("dismiss_alert");
+        let res = await invoke("dismiss_alert", {eventId: alert.id});
         console.log("DISMISS ALERT", res);
     };
 
+    const snoozeAlert = async (minutes: number) => {
+        if (!alert?.id) return;
+        let res = await invoke("snooze_alert", {eventId: alert.id, minutes});
+        console.log("SNOOZE ALERT", res);
+    };
+
+    const joinMeeting = async () => {
+        if (!alert?.id) return;
+        await invoke("join_meeting", {eventId: alert.id});
+        await closeAlert();
+    };
+
     const calculateTimeLabel = useCallback(() => {
         if (!alert) return;
 
@@ -44,15 +110,19 @@ function AlertInfo() {
 
         if (!startTime) return;
 
-        const timeDistance = formatDistance(startTime, now);
+        const labels = alert.labels ?? DEFAULT_LABELS;
+        const timeDistance = formatDistance(startTime, now, {
+            addSuffix: true,
+            locale: LOCALES[labels.language],
+        });
 
         if (now > start && now < end) {
-            setTimeLabel(`This event started ${timeDistance} ago`);
+            setTimeLabel(fill(labels.started, timeDistance));
             setRunningLate(true);
         } else if (now < start) {
-            setTimeLabel(`This event will start in ${timeDistance}`);
+            setTimeLabel(fill(labels.starts, timeDistance));
         } else if (now > end) {
-            setTimeLabel(`This event ended ${timeDistance} ago`);
+            setTimeLabel(fill(labels.ended, timeDistance));
         }
     }, [alert]);
 
@@ -61,24 +131,6 @@ function AlertInfo() {
         return () => clearInterval(intervalId);
     }, [calculateTimeLabel]);
 
-    useEffect(() => {
-        let unlisten = () => {
-        };
-        const registerListener = async () => {
-            unlisten = await listen<Schema$Event>(
-                "alert",
-                async (event) => {
-                    console.log("EVENT", event.payload);
-                    dispatch(setAlert(event.payload));
-                }
-            );
-        };
-        registerListener();
-
-        return () => {
-            // unlisten()
-        }
-    }, [dispatch])
 
     const time = useMemo(() => {
         if (!alert) return {start: "00:00", end: "00:00"};
@@ -106,17 +158,26 @@ function AlertInfo() {
     if (!alert) return;
 
     return (
-        <main
-            className="bg-background flex h-full min-h-screen flex-col items-center justify-center rounded-md p-24 backdrop-blur-md gap-3">
+        <section className="flex flex-col items-center justify-center gap-3">
             <h1 className="text-5xl">{alert?.summary ?? "No Alert to show!"}</h1>
             <p className="text-2xl text-purple-600">
-                {time.start} - {time.end} {time.endTimeSuffix}
+                {alert.time_label ?? `${time.start} - ${time.end} ${time.endTimeSuffix}`}
             </p>
+            {alert.original_time_label && (
+                <span className="text-sm text-muted-foreground">
+                    {alert.original_time_label}
+                </span>
+            )}
             {timeLabel && (
                 <span className={cn(runningLate && "text-red-500", "text-sm")}>
           {timeLabel}
         </span>
             )}
+            {alert?.warnings?.map((warning, idx) => (
+                <span key={idx} className="text-sm text-yellow-500">
+                    ⚠️ {warning}
+                </span>
+            ))}
             <div className="flex items-center justify-start gap-2 py-4">
                 {alert?.attendees?.map((attendee, idx) => (
                     <Attendee key={idx} attendee={attendee}/>
@@ -125,23 +186,45 @@ function AlertInfo() {
             {alert?.hangoutLink ? (
                 <GoogleMeetButton
                     alert={alert}
+                    label={labels.join_google_meet}
                     onClick={() => onHandleJoin(alert.hangoutLink!)}
                 />
             ) : isZoomMeeting(alert) ? (
                 <ZoomMeetButton
                     alert={alert}
+                    label={labels.join_zoom}
                     onClick={() => onHandleJoin(getZoomLink(alert))}
                 />
+            ) : alert?.join_url ? (
+                <Button
+                    variant="ghost"
+                    className="bg-primary-foreground hover:bg-secondary text-[11px] rounded-lg"
+                    onClick={joinMeeting}
+                >
+                    {labels.join_meeting}
+                </Button>
             ) : (
                 <Button
                     variant="ghost"
                     className="bg-primary-foreground hover:bg-secondary"
                     onClick={closeAlert}
                 >
-                    Ok
+                    {labels.ok}
                 </Button>
             )}
-        </main>
+            <div className="flex items-center justify-center gap-2">
+                {SNOOZE_OPTIONS.map((minutes) => (
+                    <Button
+                        key={minutes}
+                        variant="ghost"
+                        className="text-[11px] rounded-lg hover:bg-secondary"
+                        onClick={() => snoozeAlert(minutes)}
+                    >
+                        {fill(labels.snooze, minutes)}
+                    </Button>
+                ))}
+            </div>
+        </section>
     );
 }
 
@@ -162,9 +245,11 @@ const Attendee = ({attendee}: { attendee: Schema$EventAttendee }) => {
 };
 
 const GoogleMeetButton = ({
+                              label,
                               onClick,
                           }: {
     alert: Schema$Event;
+    label: string;
     onClick: () => void;
 }) => {
     return (
@@ -173,7 +258,7 @@ const GoogleMeetButton = ({
             className="bg-primary-foreground hover:bg-secondary text-[11px] rounded-lg"
             onClick={onClick}
         >
-            <span className="mr-1">Join Google meet meeting</span>{" "}
+            <span className="mr-1">{label}</span>{" "}
             <GoogleMeetIcon className="w-6 h-6 fill-transparent" style={{}}/>
         </Button>
     );
@@ -189,9 +274,11 @@ const getZoomLink = (event: Schema$Event) => {
 };
 
 const ZoomMeetButton = ({
+                            label,
                             onClick,
                         }: {
     alert: Schema$Event;
+    label: string;
     onClick: () => void;
 }) => {
     return (
@@ -201,7 +288,7 @@ const ZoomMeetButton = ({
             onClick={onClick}
         >
             <ZoomMeetIcon className="w-6 h-6 fill-transparent stroke-blue mr-2 "/>
-            <span>Join Zoom meeting</span>{" "}
+            <span>{label}</span>{" "}
         </Button>
     );
 };
@@ -209,7 +296,7 @@ const ZoomMeetButton = ({
 export default function Alert() {
     return (
         <Providers>
-            <AlertInfo/>
+            <AlertQueue/>
         </Providers>
     );
 }
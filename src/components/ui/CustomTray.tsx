@@ -14,6 +14,7 @@ import { GoogleAuthToken } from "@/slices/authSlice";
 import { useEffect, useState } from "react";
 import clsx from "clsx";
 import Calendars from "./Calendars";
+import EventDetails from "./EventDetails";
 import { Preferences } from "@/types/account";
 
 export default function CustomTrayApp() {
@@ -86,6 +87,7 @@ export default function CustomTrayApp() {
 
   return (
     <main className="flex flex-col items-start rounded-md p-1 px-2 backdrop-blur-md">
+      <EventDetails />
       <h1 className="text-xl mb-4">Preferences</h1>
       <div className="grid gap-6 w-full">
         <Accordion collapsible type="single">
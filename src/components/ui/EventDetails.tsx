@@ -0,0 +1,86 @@
+"use client";
+import { Button } from "@/components/ui/button";
+import { AlertEvent } from "@/slices/alert";
+import { listen } from "@tauri-apps/api/event";
+import { open } from "@tauri-apps/api/shell";
+import { invoke } from "@tauri-apps/api/tauri";
+import { useEffect, useState } from "react";
+
+const formatEventTime = (event: AlertEvent) => {
+  if (event.start?.date) return "All day";
+
+  const start = new Date(event.start?.dateTime ?? Date.now());
+  const end = new Date(event.end?.dateTime ?? Date.now());
+  return `${start.toLocaleTimeString([], {
+    hour: "2-digit",
+    minute: "2-digit",
+  })} - ${end.toLocaleTimeString([], { hour: "2-digit", minute: "2-digit" })}`;
+};
+
+export default function EventDetails() {
+  const [event, setEvent] = useState<AlertEvent>();
+
+  useEffect(() => {
+    let unlisten = () => {};
+    const registerListener = async () => {
+      unlisten = await listen<AlertEvent>("show-event", (event) => {
+        console.log("SHOW EVENT", event.payload);
+        setEvent(event.payload);
+      });
+    };
+    registerListener();
+
+    return () => {
+      unlisten();
+    };
+  }, []);
+
+  if (!event) return;
+
+  return (
+    <section className="flex flex-col gap-2 w-full rounded-md border p-3 mb-4">
+      <div className="flex items-start justify-between gap-2">
+        <h2 className="text-lg font-medium">{event.summary}</h2>
+        <Button
+          variant="ghost"
+          className="text-[11px] rounded-lg px-2 py-1"
+          onClick={() => setEvent(undefined)}
+        >
+          Close
+        </Button>
+      </div>
+      <p className="text-sm text-purple-600">{formatEventTime(event)}</p>
+      {event.location && <p className="text-sm">{event.location}</p>}
+      {event.warnings?.map((warning, idx) => (
+        <span key={idx} className="text-sm text-yellow-500">
+          ⚠️ {warning}
+        </span>
+      ))}
+      {event.description && (
+        <p className="text-xs whitespace-pre-line line-clamp-6">
+          {event.description}
+        </p>
+      )}
+      <div className="flex items-center gap-2">
+        {event.join_url && (
+          <Button
+            variant="ghost"
+            className="bg-primary-foreground hover:bg-secondary text-[11px] rounded-lg"
+            onClick={() => invoke("join_meeting", { eventId: event.id })}
+          >
+            Join meeting
+          </Button>
+        )}
+        {event.htmlLink && (
+          <Button
+            variant="ghost"
+            className="text-[11px] rounded-lg hover:bg-secondary"
+            onClick={() => open(event.htmlLink!)}
+          >
+            Open in Google Calendar
+          </Button>
+        )}
+      </div>
+    </section>
+  );
+}
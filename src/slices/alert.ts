@@ -1,8 +1,29 @@
 import { Schema$Event } from "@/services/api/googleCalendar";
 import { PayloadAction, createSlice } from "@reduxjs/toolkit";
 
+/** Alert page strings in the user's language, `{}` is filled by the page */
+export type AlertLabels = {
+  language: "en" | "fr" | "de" | "es";
+  ok: string;
+  join_meeting: string;
+  join_google_meet: string;
+  join_zoom: string;
+  snooze: string;
+  started: string;
+  starts: string;
+  ended: string;
+};
+
+export type AlertEvent = Schema$Event & {
+  warnings?: string[];
+  join_url?: string | null;
+  time_label?: string;
+  original_time_label?: string | null;
+  labels?: AlertLabels;
+};
+
 type AlertState = {
-  alert?: Schema$Event | undefined;
+  alert?: AlertEvent | undefined;
 };
 
 const initialState: AlertState = {};
@@ -11,7 +32,7 @@ const alert = createSlice({
   name: "alert",
   initialState,
   reducers: {
-    setAlert: (state, { payload }: PayloadAction<Schema$Event>) => {
+    setAlert: (state, { payload }: PayloadAction<AlertEvent>) => {
       state.alert = payload;
     },
     removeAlert: (state) => {
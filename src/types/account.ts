@@ -2,8 +2,39 @@ export type Preferences = {
   storage_path: String;
   notify_only_meetings: boolean;
   accounts_preferences: { [key: string]: AccountPreference };
+  wrap_up_minutes?: number | null;
+  pending_invitations: InvitationPolicy;
+  all_day_events: AllDayPolicy;
+  morning_notification_hour?: number | null;
+  tray_title: TrayTitleFormat;
+  tray_title_max_length?: number | null;
+  event_click_action: EventClickAction;
+  agenda_days?: number | null;
+  time_format: TimeFormat;
+  language: Language;
+  timezone?: string | null;
 };
 
+export type TimeFormat = "twelve_hour" | "twenty_four_hour";
+
+export type Language = "en" | "fr" | "de" | "es";
+
+export type EventClickAction = "show_details" | "open_in_calendar";
+
+export type TrayTitleFormat = "countdown" | "next_event_title" | "icon_only";
+
+export type AllDayPolicy = "off" | "morning_notification";
+
+export type InvitationPolicy = "alert" | "notify_only" | "ignore";
+
 export type AccountPreference = {
   hidden_calendars: string[];
+  lead_times: LeadTimes;
+  calendar_lead_times: { [calendarId: string]: LeadTimes };
+  use_google_reminders: boolean;
+};
+
+export type LeadTimes = {
+  notification: number;
+  alert: number;
 };
@@ -0,0 +1,13 @@
+import { Schema$Event } from "@/services/api/googleCalendar";
+
+/** Events grouped by the backend, `later` runs up to the agenda horizon */
+export type EventGroups = {
+  all_day: Schema$Event[];
+  now: Schema$Event[];
+  upcoming: Schema$Event[];
+  tomorrow: Schema$Event[];
+  later: Schema$Event[];
+  /** Events come from the local cache because the last sync failed */
+  stale: boolean;
+  synced_at?: string | null;
+};
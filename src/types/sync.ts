@@ -0,0 +1,11 @@
+export type SyncState = "pending" | "syncing" | "synced" | "failing" | "disabled";
+
+export type AccountSyncStatus = {
+  account: string;
+  state: SyncState;
+  last_synced_at?: string;
+  last_error?: string;
+  retry_at?: string;
+  token_expires_at?: string;
+  calendar_count: number;
+};